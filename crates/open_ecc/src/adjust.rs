@@ -0,0 +1,191 @@
+use crate::{
+    error::{Error, Result},
+    units::{Brightness, Kelvin},
+};
+use std::{fmt, str::FromStr};
+
+/// Named temperatures in Kelvin, clamped to the range of the light when used
+pub const TEMPERATURE_PRESETS: &[(&str, u16)] = &[
+    ("candle", 1900),
+    ("tungsten", 3200),
+    ("halogen", 3400),
+    ("fluorescent", 4000),
+    ("daylight", 5600),
+    ("overcast", 6500),
+    ("shade", 7500),
+];
+
+/// A brightness, absolute or relative to the current one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BrightnessValue {
+    /// `50` or `50%`
+    Absolute(Brightness),
+    /// `+10` or `-10` points
+    Relative(i16),
+    /// `+25%` or `-25%` of the current brightness
+    Scale(f32),
+}
+
+impl BrightnessValue {
+    /// Brightness to set, given the current one
+    pub fn resolve(&self, current: Brightness) -> Brightness {
+        let current = current.get() as f32;
+        match *self {
+            Self::Absolute(x) => x,
+            Self::Relative(x) => Brightness::clamped((current + x as f32).round() as i32),
+            Self::Scale(x) => Brightness::clamped((current * (1.0 + x / 100.0)).round() as i32),
+        }
+    }
+}
+
+/// Parses `50`, `50%`, `+10`, `-10`, `+25%` or `-25%`
+impl FromStr for BrightnessValue {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let invalid = || Error::Validation(format!("Invalid brightness '{}'", s));
+        let (number, percent) = match s.strip_suffix('%') {
+            Some(x) => (x, true),
+            None => (s, false),
+        };
+
+        if number.starts_with(['+', '-']) {
+            let value = number.parse::<i16>().map_err(|_| invalid())?;
+            return Ok(match percent {
+                true => Self::Scale(value as f32),
+                false => Self::Relative(value),
+            });
+        }
+
+        let value = number.parse::<u8>().map_err(|_| invalid())?;
+        Ok(Self::Absolute(Brightness::new(value)?))
+    }
+}
+
+impl fmt::Display for BrightnessValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Absolute(x) => write!(f, "{}", x.get()),
+            Self::Relative(x) => write!(f, "{:+}", x),
+            Self::Scale(x) => write!(f, "{:+}%", x),
+        }
+    }
+}
+
+/// A temperature, absolute or relative to the current one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemperatureValue {
+    /// `3200`, `3200k`, `250mired` or a preset such as `tungsten`
+    Absolute(u16),
+    /// `+200` or `-200` Kelvin
+    Relative(i32),
+    /// `+20mired` or `-20mired`, a positive value is warmer
+    RelativeMired(i32),
+}
+
+impl TemperatureValue {
+    /// Temperature to set given the current one, clamped to the range of `Kelvin`.
+    /// Relative values need a current temperature, which lights showing a colour don't have.
+    pub fn resolve(&self, current: Option<Kelvin>) -> Option<Kelvin> {
+        let current = current.map(|x| x.get() as f64);
+        let value = match *self {
+            Self::Absolute(x) => x as f64,
+            Self::Relative(x) => current? + x as f64,
+            Self::RelativeMired(x) => {
+                let mired = 1e6 / current? + x as f64;
+                1e6 / mired.max(1.0)
+            }
+        };
+        Some(Kelvin::clamped(value.round() as i32))
+    }
+}
+
+/// Parses `3200`, `3200k`, `+200`, `-200`, `250mired`, `+20mired` or a preset such as `daylight`
+impl FromStr for TemperatureValue {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_ascii_lowercase();
+        let invalid = || Error::Validation(format!("Invalid temperature '{}'", s));
+
+        if let Some((_, value)) = TEMPERATURE_PRESETS.iter().find(|(name, _)| *name == s) {
+            return Ok(Self::Absolute(*value));
+        }
+
+        if let Some(number) = s.strip_suffix("mired") {
+            let value = number.trim().parse::<i32>().map_err(|_| invalid())?;
+            if number.starts_with(['+', '-']) {
+                return Ok(Self::RelativeMired(value));
+            }
+            if value <= 0 {
+                return Err(invalid());
+            }
+            return Ok(Self::Absolute((1e6 / value as f64).round() as u16));
+        }
+
+        let number = s.strip_suffix('k').unwrap_or(&s);
+        if number.starts_with(['+', '-']) {
+            return number
+                .parse::<i32>()
+                .map(Self::Relative)
+                .map_err(|_| invalid());
+        }
+        number
+            .parse::<u16>()
+            .map(Self::Absolute)
+            .map_err(|_| invalid())
+    }
+}
+
+impl fmt::Display for TemperatureValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Absolute(x) => write!(f, "{}K", x),
+            Self::Relative(x) => write!(f, "{:+}K", x),
+            Self::RelativeMired(x) => write!(f, "{:+}mired", x),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brightness() {
+        let parse = |s: &str| s.parse::<BrightnessValue>().unwrap();
+        let resolve =
+            |s: &str, current: u8| parse(s).resolve(Brightness::new(current).unwrap()).get();
+        assert_eq!(resolve("50", 10), 50);
+        assert_eq!(resolve("50%", 10), 50);
+        assert_eq!(resolve("+10", 95), 100);
+        assert_eq!(resolve("-10", 5), 0);
+        assert_eq!(resolve("-25%", 40), 30);
+        assert_eq!(resolve("+50%", 40), 60);
+        assert!("101".parse::<BrightnessValue>().is_err());
+        assert!("bright".parse::<BrightnessValue>().is_err());
+    }
+
+    #[test]
+    fn test_temperature() {
+        let resolve = |s: &str, current: Option<u16>| {
+            s.parse::<TemperatureValue>()
+                .unwrap()
+                .resolve(current.map(|x| Kelvin::new(x).unwrap()))
+                .map(|x| x.get())
+        };
+        assert_eq!(resolve("3200", None), Some(3200));
+        assert_eq!(resolve("3200K", None), Some(3200));
+        assert_eq!(resolve("+200", Some(4000)), Some(4200));
+        assert_eq!(resolve("-200", Some(3000)), Some(2900));
+        assert_eq!(resolve("250mired", None), Some(4000));
+        assert_eq!(resolve("+50mired", Some(4000)), Some(3333));
+        assert_eq!(resolve("tungsten", None), Some(3200));
+        assert_eq!(resolve("Candle", None), Some(2900));
+        assert_eq!(resolve("shade", None), Some(7000));
+        assert_eq!(resolve("+200", None), None);
+        assert!("0mired".parse::<TemperatureValue>().is_err());
+        assert!("warmish".parse::<TemperatureValue>().is_err());
+    }
+}
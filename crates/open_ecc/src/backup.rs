@@ -0,0 +1,190 @@
+use crate::{
+    contracts::AccessoryInfoGet,
+    error::{Error, Result},
+    identity::Identity,
+    light::{GroupReport, LightGroup},
+    snapshot::DeviceSnapshot,
+};
+use serde::{Deserialize, Serialize};
+
+/// Format version written by this crate, older versions can still be read
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Identity, settings and state of a set of devices
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backup {
+    pub version: u32,
+    pub devices: Vec<DeviceBackup>,
+}
+
+/// Identity, settings and state of one device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceBackup {
+    pub accessory_info: AccessoryInfoGet,
+    /// Lights and settings, including the favourites
+    pub state: DeviceSnapshot,
+}
+
+impl Backup {
+    // Constructors
+
+    pub fn new(devices: Vec<DeviceBackup>) -> Self {
+        Self {
+            version: BACKUP_VERSION,
+            devices,
+        }
+    }
+
+    // Public
+
+    /// Fails on files written by a newer version
+    pub fn validate(&self) -> Result<()> {
+        if self.version > BACKUP_VERSION {
+            return Err(Error::Validation(format!(
+                "Backup version {} is newer than the supported version {}",
+                self.version, BACKUP_VERSION
+            )));
+        }
+        Ok(())
+    }
+
+    /// Device with the same serial number or MAC address
+    pub fn find(&self, accessory_info: &AccessoryInfoGet) -> Option<&DeviceBackup> {
+        self.devices.iter().find(|x| x.matches(accessory_info))
+    }
+
+    /// Device by serial number or MAC address
+    pub fn find_by_id(&self, id: &str) -> Option<&DeviceBackup> {
+        self.devices
+            .iter()
+            .find(|x| Identity::from(&x.accessory_info).is(id))
+    }
+
+    /// Restore every light of the group from the device it was backed up as
+    pub async fn restore<'a>(&self, group: &LightGroup<'a>) -> GroupReport<'a, ()> {
+        group
+            .run(|light| async move {
+                let accessory_info = light.accessory_info_get().await?;
+                let device = self.find(&accessory_info).ok_or_else(|| {
+                    Error::Validation(format!(
+                        "No backup for serial number {}",
+                        accessory_info.serial_number
+                    ))
+                })?;
+                light.restore_backup(device).await
+            })
+            .await
+    }
+}
+
+impl DeviceBackup {
+    /// Whether this is the device described by `accessory_info`, by serial number or MAC address
+    pub fn matches(&self, accessory_info: &AccessoryInfoGet) -> bool {
+        Identity::from(&self.accessory_info).matches(accessory_info)
+    }
+
+    /// Copy the settings and favourites of this device onto every light of the group
+    pub async fn clone_settings<'a>(&self, group: &LightGroup<'a>) -> GroupReport<'a, ()> {
+        group
+            .run(|light| async move {
+                let settings =
+                    self.state.settings.clone().ok_or_else(|| {
+                        Error::Validation("The backup has no settings".to_string())
+                    })?;
+                light.settings_set(&settings.into()).await
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contracts::{Favourite, LightGet, LightsGet, LightsSettingsGet, RemoteControl},
+        units::{ApiTemperature, Brightness},
+    };
+
+    fn device(serial_number: &str, mac_address: &str) -> DeviceBackup {
+        DeviceBackup {
+            accessory_info: AccessoryInfoGet {
+                serial_number: serial_number.to_string(),
+                mac_address: mac_address.to_string(),
+                ..Default::default()
+            },
+            state: DeviceSnapshot::default(),
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        let backup = Backup::new(vec![
+            device("BW001", "3C:6A:9D:00:00:01"),
+            device("", "3C:6A:9D:00:00:02"),
+        ]);
+        let info = |serial_number: &str, mac_address: &str| AccessoryInfoGet {
+            serial_number: serial_number.to_string(),
+            mac_address: mac_address.to_string(),
+            ..Default::default()
+        };
+
+        // Either the serial number or the MAC address is enough, in any notation
+        assert!(backup.find(&info("BW001", "")).is_some());
+        assert!(backup.find(&info("", "3c-6a-9d-00-00-02")).is_some());
+        assert!(backup.find(&info("", "")).is_none());
+        assert!(backup.find(&info("BW003", "3C:6A:9D:00:00:03")).is_none());
+        assert!(backup.find_by_id("3c6a9d000001").is_some());
+    }
+
+    #[test]
+    fn test_toml() {
+        let light = |temperature: Option<u16>, hue: Option<f32>| LightGet {
+            on: true,
+            brightness: Brightness::new(40).unwrap(),
+            temperature: temperature.map(|x| ApiTemperature::new(x).unwrap()),
+            hue,
+            saturation: hue.map(|_| 100.0),
+        };
+        let mut white = device("BW001", "3C:6A:9D:00:00:01");
+        white.state.lights = LightsGet {
+            number_of_lights: 2,
+            lights: vec![light(Some(200), None), light(Some(250), None)],
+        };
+        white.state.settings = Some(LightsSettingsGet {
+            remote_control: RemoteControl {
+                favourites: vec![
+                    Favourite {
+                        brightness: Brightness::new(20).unwrap(),
+                        temperature: ApiTemperature::new(143).unwrap(),
+                    },
+                    Favourite {
+                        brightness: Brightness::new(80).unwrap(),
+                        temperature: ApiTemperature::new(344).unwrap(),
+                    },
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let mut color = device("BW002", "3C:6A:9D:00:00:02");
+        color.state.lights = LightsGet {
+            number_of_lights: 1,
+            lights: vec![light(None, Some(30.0))],
+        };
+        let backup = Backup::new(vec![white, color]);
+
+        let text = toml::to_string(&backup).unwrap();
+        assert!(text.contains("[[devices]]"));
+        assert_eq!(toml::from_str::<Backup>(&text).unwrap(), backup);
+    }
+
+    #[test]
+    fn test_version() {
+        let mut backup = Backup::new(Vec::new());
+        assert!(backup.validate().is_ok());
+        backup.version = BACKUP_VERSION + 1;
+        assert!(backup.validate().is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    catalogue::Product,
+    contracts::AccessoryInfoGet,
+    error::{Error, Result},
+};
+use std::fmt;
+
+/// Something a light may or may not be able to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// White light with an adjustable colour temperature
+    Temperature,
+    /// Hue and saturation
+    Color,
+    /// Animated scenes
+    Scenes,
+    /// Battery info and energy saving
+    Battery,
+    /// More than one light on the device
+    MultiLight,
+}
+
+impl Feature {
+    /// Name used in `Error::Unsupported`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Temperature => "temperature",
+            Self::Color => "colour",
+            Self::Scenes => "scenes",
+            Self::Battery => "battery",
+            Self::MultiLight => "multiple lights",
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// What a device supports, derived from its accessory info
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub temperature: bool,
+    pub color: bool,
+    pub scenes: bool,
+    pub battery: bool,
+    pub multi_light: bool,
+}
+
+impl Capabilities {
+    // Constructors
+
+    /// Capabilities of the model in the product catalogue, found by board type or product name,
+    /// or else guessed from the features.
+    /// Features reported by the device are added on top.
+    pub fn from_accessory_info(info: &AccessoryInfoGet) -> Self {
+        let has = |feature: &str| {
+            info.features
+                .iter()
+                .any(|x| x.eq_ignore_ascii_case(feature))
+        };
+
+        match Product::from_accessory_info(info) {
+            Some(product) => Self {
+                temperature: true,
+                color: product.color || has("color"),
+                scenes: product.scenes || has("scenes"),
+                battery: product.battery || has("battery"),
+                multi_light: false,
+            },
+            // Every known product has a white mode, unless it says otherwise
+            None => Self {
+                temperature: !has("color") || has("temperature"),
+                color: has("color"),
+                scenes: has("scenes"),
+                battery: has("battery"),
+                multi_light: false,
+            },
+        }
+    }
+
+    /// Mark the device as having more than one light when `count` is above one
+    pub fn lights(mut self, count: usize) -> Self {
+        self.multi_light = count > 1;
+        self
+    }
+
+    // Public
+
+    pub fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Temperature => self.temperature,
+            Feature::Color => self.color,
+            Feature::Scenes => self.scenes,
+            Feature::Battery => self.battery,
+            Feature::MultiLight => self.multi_light,
+        }
+    }
+
+    /// `Error::Unsupported` unless the device supports `feature`
+    pub fn require(&self, feature: Feature) -> Result<()> {
+        match self.supports(feature) {
+            true => Ok(()),
+            false => Err(Error::Unsupported(feature.name())),
+        }
+    }
+
+    /// Supported features, in declaration order
+    pub fn features(&self) -> Vec<Feature> {
+        [
+            Feature::Temperature,
+            Feature::Color,
+            Feature::Scenes,
+            Feature::Battery,
+            Feature::MultiLight,
+        ]
+        .into_iter()
+        .filter(|x| self.supports(*x))
+        .collect()
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let features = self
+            .features()
+            .iter()
+            .map(Feature::name)
+            .collect::<Vec<_>>();
+        match features.is_empty() {
+            true => write!(f, "brightness only"),
+            false => write!(f, "{}", features.join(", ")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(product_name: &str, hardware_board_type: u16, features: &[&str]) -> AccessoryInfoGet {
+        AccessoryInfoGet {
+            product_name: product_name.to_string(),
+            hardware_board_type,
+            features: features.iter().map(|x| x.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_known_products() {
+        let key_light =
+            Capabilities::from_accessory_info(&info("Elgato Key Light", 53, &["lights"]));
+        assert_eq!(key_light.features(), vec![Feature::Temperature]);
+        assert!(key_light.require(Feature::Color).is_err());
+
+        let strip = Capabilities::from_accessory_info(&info("Elgato Light Strip", 70, &["lights"]));
+        assert!(strip.color && strip.scenes && strip.temperature && !strip.battery);
+
+        let mini =
+            Capabilities::from_accessory_info(&info("Elgato Key Light Mini", 202, &["lights"]));
+        assert!(mini.battery && !mini.color);
+        // Renamed, but the board type is still that of a Light Strip
+        let renamed = Capabilities::from_accessory_info(&info("Desk", 70, &["lights"]));
+        assert_eq!(renamed, strip);
+    }
+
+    #[test]
+    fn test_features() {
+        let capabilities =
+            Capabilities::from_accessory_info(&info("Unknown", 0, &["lights", "color", "battery"]))
+                .lights(2);
+        assert!(!capabilities.temperature);
+        assert!(capabilities.color && capabilities.battery && capabilities.multi_light);
+        assert_eq!(capabilities.to_string(), "colour, battery, multiple lights");
+    }
+}
@@ -0,0 +1,113 @@
+use crate::contracts::AccessoryInfoGet;
+
+/// An Elgato model and what it supports.
+/// Every model documents the same 2900K - 7000K range that `Kelvin` enforces,
+/// so temperatures are validated and converted the same way on all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Product {
+    pub name: &'static str,
+    /// `hardware_board_type` reported by the model, empty where only the name is known
+    pub board_types: &'static [u16],
+    pub color: bool,
+    pub scenes: bool,
+    pub battery: bool,
+}
+
+/// Known models, all with a white mode
+pub const PRODUCTS: &[Product] = &[
+    Product {
+        name: "Elgato Key Light",
+        board_types: &[53],
+        color: false,
+        scenes: false,
+        battery: false,
+    },
+    Product {
+        name: "Elgato Key Light Air",
+        board_types: &[],
+        color: false,
+        scenes: false,
+        battery: false,
+    },
+    Product {
+        name: "Elgato Ring Light",
+        board_types: &[],
+        color: false,
+        scenes: false,
+        battery: false,
+    },
+    Product {
+        name: "Elgato Key Light Mini",
+        board_types: &[202],
+        color: false,
+        scenes: false,
+        battery: true,
+    },
+    Product {
+        name: "Elgato Light Strip",
+        board_types: &[70],
+        color: true,
+        scenes: true,
+        battery: false,
+    },
+];
+
+impl Product {
+    // Constructors
+
+    /// Known model with the board type, or else the product name, of the accessory info
+    pub fn from_accessory_info(info: &AccessoryInfoGet) -> Option<&'static Self> {
+        Self::by_board_type(info.hardware_board_type).or_else(|| Self::by_name(&info.product_name))
+    }
+
+    pub fn by_board_type(board_type: u16) -> Option<&'static Self> {
+        PRODUCTS
+            .iter()
+            .find(|x| x.board_types.contains(&board_type))
+    }
+
+    /// Case insensitive, with or without the `Elgato` prefix
+    pub fn by_name(name: &str) -> Option<&'static Self> {
+        let name = name.trim();
+        PRODUCTS.iter().find(|x| {
+            x.name.eq_ignore_ascii_case(name)
+                || x.name
+                    .strip_prefix("Elgato ")
+                    .is_some_and(|x| x.eq_ignore_ascii_case(name))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(Product::by_board_type(53).unwrap().name, "Elgato Key Light");
+        assert!(Product::by_board_type(999).is_none());
+        assert_eq!(
+            Product::by_name("key light mini").unwrap().name,
+            "Elgato Key Light Mini"
+        );
+        assert!(Product::by_name("Key Light Neo").is_none());
+
+        // The board type wins over a renamed product, the name is used for unknown boards
+        let info = |product_name: &str, hardware_board_type| AccessoryInfoGet {
+            product_name: product_name.to_string(),
+            hardware_board_type,
+            ..Default::default()
+        };
+        assert!(
+            Product::from_accessory_info(&info("Desk", 70))
+                .unwrap()
+                .color
+        );
+        assert!(
+            Product::from_accessory_info(&info("Elgato Light Strip", 999))
+                .unwrap()
+                .color
+        );
+        assert!(Product::from_accessory_info(&info("Desk", 999)).is_none());
+    }
+}
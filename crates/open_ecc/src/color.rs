@@ -0,0 +1,416 @@
+use crate::error::{Error, Result};
+use std::{fmt, str::FromStr};
+
+/// Colour as the lights understand it
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Hsv {
+    /// Range: 0 - 360
+    pub hue: f32,
+    /// Range: 0 - 100
+    pub saturation: f32,
+    /// Range: 0 - 100, sent to the light as brightness
+    pub value: f32,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Hsv {
+    pub fn new(hue: f32, saturation: f32, value: f32) -> Result<Self> {
+        if !(0.0..=360.0).contains(&hue) {
+            return Err(Error::Validation(format!(
+                "Hue {} is outside of 0 - 360",
+                hue
+            )));
+        }
+        if !(0.0..=100.0).contains(&saturation) {
+            return Err(Error::Validation(format!(
+                "Saturation {} is outside of 0 - 100",
+                saturation
+            )));
+        }
+        if !(0.0..=100.0).contains(&value) {
+            return Err(Error::Validation(format!(
+                "Value {} is outside of 0 - 100",
+                value
+            )));
+        }
+        Ok(Self {
+            hue,
+            saturation,
+            value,
+        })
+    }
+}
+
+impl Rgb {
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Parse `#rrggbb` or `#rgb`, with or without the `#`
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let expanded = match digits.len() {
+            3 => digits.chars().flat_map(|x| [x, x]).collect::<String>(),
+            6 => digits.to_string(),
+            _ => return Err(Error::Validation(format!("Invalid hex colour '{}'", hex))),
+        };
+        let value = u32::from_str_radix(&expanded, 16)
+            .map_err(|_| Error::Validation(format!("Invalid hex colour '{}'", hex)))?;
+        Ok(Self::from(value))
+    }
+
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl From<u32> for Rgb {
+    fn from(value: u32) -> Self {
+        Self {
+            r: (value >> 16) as u8,
+            g: (value >> 8) as u8,
+            b: value as u8,
+        }
+    }
+}
+
+impl From<Rgb> for Hsv {
+    fn from(rgb: Rgb) -> Self {
+        let r = rgb.r as f32 / 255.0;
+        let g = rgb.g as f32 / 255.0;
+        let b = rgb.b as f32 / 255.0;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+
+        Self {
+            hue,
+            saturation: saturation * 100.0,
+            value: max * 100.0,
+        }
+    }
+}
+
+impl From<Hsv> for Rgb {
+    fn from(hsv: Hsv) -> Self {
+        let s = hsv.saturation / 100.0;
+        let v = hsv.value / 100.0;
+        let c = v * s;
+        let h = hsv.hue.rem_euclid(360.0) / 60.0;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u8 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = v - c;
+        let channel = |x: f32| ((x + m) * 255.0).round() as u8;
+        Self {
+            r: channel(r),
+            g: channel(g),
+            b: channel(b),
+        }
+    }
+}
+
+/// Parses `#ff8800`, `#f80`, `ff8800`, `hsv(30,100,100)`, `rgb(255,136,0)` or a CSS colour name
+impl FromStr for Hsv {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_ascii_lowercase();
+        if let Some(args) = function_args(&s, "hsv") {
+            let [hue, saturation, value] = parse_args::<f32>(&s, &args)?;
+            return Self::new(hue, saturation, value);
+        }
+        s.parse::<Rgb>().map(Self::from)
+    }
+}
+
+/// Parses `#ff8800`, `#f80`, `ff8800`, `rgb(255,136,0)` or a CSS colour name
+impl FromStr for Rgb {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_ascii_lowercase();
+        if let Some(args) = function_args(&s, "rgb") {
+            let [r, g, b] = parse_args::<u8>(&s, &args)?;
+            return Ok(Self::new(r, g, b));
+        }
+        if s.starts_with('#') {
+            return Self::from_hex(&s);
+        }
+        if let Some((_, value)) = CSS_COLORS.iter().find(|(name, _)| *name == s) {
+            return Ok(Self::from(*value));
+        }
+        // Without the `#`, only six digits are read as hex so that short words aren't
+        if s.len() == 6 && s.chars().all(|x| x.is_ascii_hexdigit()) {
+            return Self::from_hex(&s);
+        }
+        Err(Error::Validation(format!("Unknown colour '{}'", s)))
+    }
+}
+
+impl fmt::Display for Hsv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hsv({}, {}, {})",
+            self.hue.round(),
+            self.saturation.round(),
+            self.value.round()
+        )
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+fn function_args(s: &str, name: &str) -> Option<Vec<String>> {
+    let inner = s
+        .strip_prefix(name)?
+        .trim()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    Some(
+        inner
+            .split(',')
+            .map(|x| x.trim().trim_end_matches('%').to_string())
+            .collect(),
+    )
+}
+
+fn parse_args<T: FromStr>(s: &str, args: &[String]) -> Result<[T; 3]> {
+    let invalid = || Error::Validation(format!("Invalid colour '{}'", s));
+    let values = args
+        .iter()
+        .map(|x| x.parse::<T>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>>>()?;
+    values.try_into().map_err(|_| invalid())
+}
+
+/// CSS named colours
+const CSS_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_formats() {
+        let expected = Hsv::from(Rgb::new(0xff, 0x88, 0x00));
+        assert_eq!("#ff8800".parse::<Hsv>().unwrap(), expected);
+        assert_eq!("#F80".parse::<Hsv>().unwrap(), expected);
+        assert_eq!("rgb(255, 136, 0)".parse::<Hsv>().unwrap(), expected);
+        assert_eq!(
+            "hsv(30,100%,100%)".parse::<Hsv>().unwrap(),
+            Hsv::new(30.0, 100.0, 100.0).unwrap()
+        );
+        assert_eq!(
+            "RebeccaPurple".parse::<Rgb>().unwrap(),
+            Rgb::new(0x66, 0x33, 0x99)
+        );
+        assert!("hsv(400,100,100)".parse::<Hsv>().is_err());
+        assert!("notacolour".parse::<Hsv>().is_err());
+    }
+
+    #[test]
+    fn test_parse_bare_hex() {
+        assert_eq!("ff8800".parse::<Rgb>().unwrap(), Rgb::new(0xff, 0x88, 0x00));
+        // Words that happen to be hex digits are only colours with a `#`
+        for word in ["bad", "add", "fade"] {
+            assert!(word.parse::<Rgb>().is_err(), "Parsed {}", word);
+        }
+        assert!("#add".parse::<Rgb>().is_ok());
+        assert!(
+            matches!("bad".parse::<Hsv>(), Err(Error::Validation(e)) if e == "Unknown colour 'bad'")
+        );
+    }
+
+    #[test]
+    fn test_rgb_hsv_roundtrip() {
+        for (_, value) in CSS_COLORS {
+            let rgb = Rgb::from(*value);
+            assert_eq!(Rgb::from(Hsv::from(rgb)), rgb, "Failed at {}", rgb);
+        }
+    }
+
+    #[test]
+    fn test_hsv_from_rgb() {
+        let hsv = Hsv::from(Rgb::new(0, 0, 255));
+        assert_eq!(hsv, Hsv::new(240.0, 100.0, 100.0).unwrap());
+        assert_eq!(Rgb::new(0xff, 0x88, 0x00).to_hex(), "#ff8800");
+    }
+}
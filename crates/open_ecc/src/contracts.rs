@@ -1,9 +1,11 @@
-use crate::serialization::{
-    temperature_handler, temperature_option_handler, u8_bool_handler, u8_bool_option_handler,
+use crate::{
+    serialization::{u8_bool_handler, u8_bool_option_handler},
+    units::{ApiTemperature, Brightness},
 };
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct LightsGet {
     pub number_of_lights: u8,
@@ -16,73 +18,156 @@ pub struct LightsPut {
     pub lights: Vec<LightPut>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct LightGet {
     /// State
     #[serde(with = "u8_bool_handler")]
     pub on: bool,
     /// Brightness
-    pub brightness: u8,
-    #[serde(with = "temperature_handler")]
-    /// Range: 2900 - 7000 Kelvin
-    pub temperature: u16,
+    pub brightness: Brightness,
+    /// Absent on lights in colour mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<ApiTemperature>,
+    /// Range: 0 - 360, only reported by colour lights
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hue: Option<f32>,
+    /// Range: 0 - 100, only reported by colour lights
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saturation: Option<f32>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct LightPut {
     /// State
     #[serde(
+        default,
         skip_serializing_if = "Option::is_none",
         with = "u8_bool_option_handler"
     )]
     pub on: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<Brightness>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<ApiTemperature>,
+    /// Range: 0 - 360
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hue: Option<f32>,
     /// Range: 0 - 100
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub brightness: Option<u8>,
-    /// Range: 2900 - 7000 Kelvin
-    #[serde(
-        skip_serializing_if = "Option::is_none",
-        with = "temperature_option_handler"
-    )]
-    pub temperature: Option<u16>,
+    pub saturation: Option<f32>,
+    /// Animated scene, only supported by Light Strips
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub scene: Option<Scene>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub struct LightsSettingsGet {
-    /// State: 0 | 1
-    pub power_on_behavior: u8,
+pub struct Scene {
+    /// Identifier, for example `com.example.sunrise`
+    pub id: String,
+    pub name: String,
+    /// Must match the number of frames
+    #[serde(default)]
+    pub number_of_scene_elements: u16,
+    #[serde(rename = "scene", alias = "frames")]
+    pub frames: Vec<SceneFrame>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneFrame {
+    /// Colour of each segment along the strip, repeated if shorter than the strip
+    pub segments: Vec<SegmentColor>,
+    /// How long the frame is shown
+    pub duration_ms: u32,
+    /// How long the fade into the frame takes
+    #[serde(default)]
+    pub transition_ms: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentColor {
+    /// Range: 0 - 360
+    pub hue: f32,
     /// Range: 0 - 100
-    pub power_on_brightness: u8,
-    /// Range: 2900 - 7000 (increments of 50)
-    #[serde(with = "temperature_handler")]
-    pub power_on_temperature: u16,
+    pub saturation: f32,
+    /// Range: 0 - 100
+    pub brightness: f32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LightsSettingsGet {
+    pub power_on_behavior: PowerOnBehavior,
+    pub power_on_brightness: Brightness,
+    pub power_on_temperature: ApiTemperature,
     pub switch_on_duration_ms: u16,
     pub switch_off_duration_ms: u16,
     pub color_change_duration_ms: u16,
     pub remote_control: RemoteControl,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+/// What the light does when it gets power
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "u8", into = "u8")]
+pub enum PowerOnBehavior {
+    /// Stay off
+    Off,
+    /// Turn on with the power-on brightness and temperature
+    #[default]
+    On,
+    /// A value this library doesn't know about, kept as it is
+    Other(u8),
+}
+
+impl From<u8> for PowerOnBehavior {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Off,
+            1 => Self::On,
+            x => Self::Other(x),
+        }
+    }
+}
+
+impl fmt::Display for PowerOnBehavior {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Off => write!(f, "off"),
+            Self::On => write!(f, "on"),
+            Self::Other(x) => write!(f, "unknown ({})", x),
+        }
+    }
+}
+
+impl From<PowerOnBehavior> for u8 {
+    fn from(value: PowerOnBehavior) -> Self {
+        match value {
+            PowerOnBehavior::Off => 0,
+            PowerOnBehavior::On => 1,
+            PowerOnBehavior::Other(x) => x,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoteControl {
     pub favourites: Vec<Favourite>,
     pub auto_mode: AutoMode,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Favourite {
-    /// Range: 0 - 100
-    pub brightness: u8,
-    /// Range: 2900 - 7000 (increments of 50)
-    #[serde(with = "temperature_handler")]
-    pub temperature: u16,
+    pub brightness: Brightness,
+    pub temperature: ApiTemperature,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AutoMode {
     pub target_lux_value: u16,
@@ -91,16 +176,12 @@ pub struct AutoMode {
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct LightsSettingsPut {
-    /// State: 0 | 1
-    pub power_on_behavior: Option<u8>,
-    /// Range: 0 - 100
-    pub power_on_brightness: Option<u8>,
-    /// Range: 2900 - 7000 (increments of 50)
-    #[serde(
-        skip_serializing_if = "Option::is_none",
-        with = "temperature_option_handler"
-    )]
-    pub power_on_temperature: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power_on_behavior: Option<PowerOnBehavior>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power_on_brightness: Option<Brightness>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power_on_temperature: Option<ApiTemperature>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub switch_on_duration_ms: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -123,25 +204,239 @@ pub struct RemoteControlPut {
 #[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub struct FavouritePut {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<Brightness>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<ApiTemperature>,
+}
+
+impl From<LightsSettingsGet> for LightsSettingsPut {
+    fn from(value: LightsSettingsGet) -> Self {
+        Self {
+            power_on_behavior: Some(value.power_on_behavior),
+            power_on_brightness: Some(value.power_on_brightness),
+            power_on_temperature: Some(value.power_on_temperature),
+            switch_on_duration_ms: Some(value.switch_on_duration_ms),
+            switch_off_duration_ms: Some(value.switch_off_duration_ms),
+            color_change_duration_ms: Some(value.color_change_duration_ms),
+            remote_control: Some(value.remote_control.into()),
+        }
+    }
+}
+
+impl From<RemoteControl> for RemoteControlPut {
+    fn from(value: RemoteControl) -> Self {
+        Self {
+            favourites: Some(value.favourites.into_iter().map(Into::into).collect()),
+            auto_mode: Some(AutoModePut {
+                target_lux_value: Some(value.auto_mode.target_lux_value),
+            }),
+        }
+    }
+}
+
+impl From<Favourite> for FavouritePut {
+    fn from(value: Favourite) -> Self {
+        Self {
+            brightness: Some(value.brightness),
+            temperature: Some(value.temperature),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoModePut {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_lux_value: Option<u16>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatteryInfo {
+    pub power_source: PowerSource,
+    /// Charge in percent, range: 0 - 100
+    pub level: f32,
+    pub status: BatteryStatus,
+    /// Millivolts
+    #[serde(default)]
+    pub current_battery_voltage: u32,
+    /// Millivolts
+    #[serde(default)]
+    pub input_charge_voltage: u32,
+    /// Milliamps
+    #[serde(default)]
+    pub input_charge_current: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "u8", into = "u8")]
+pub enum PowerSource {
+    #[default]
+    Unknown,
+    Mains,
+    Battery,
+    /// A value this library doesn't know about, kept as it is
+    Other(u8),
+}
+
+impl From<u8> for PowerSource {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Unknown,
+            1 => Self::Mains,
+            2 => Self::Battery,
+            x => Self::Other(x),
+        }
+    }
+}
+
+impl From<PowerSource> for u8 {
+    fn from(value: PowerSource) -> Self {
+        match value {
+            PowerSource::Unknown => 0,
+            PowerSource::Mains => 1,
+            PowerSource::Battery => 2,
+            PowerSource::Other(x) => x,
+        }
+    }
+}
+
+impl fmt::Display for PowerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown => write!(f, "unknown"),
+            Self::Mains => write!(f, "mains"),
+            Self::Battery => write!(f, "battery"),
+            Self::Other(x) => write!(f, "unknown ({})", x),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "u8", into = "u8")]
+pub enum BatteryStatus {
+    #[default]
+    Draining,
+    Charging,
+    /// Measuring the charge level, shortly after the power source changes
+    CheckingCharge,
+    /// A value this library doesn't know about, kept as it is
+    Other(u8),
+}
+
+impl From<u8> for BatteryStatus {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Draining,
+            2 => Self::Charging,
+            3 => Self::CheckingCharge,
+            x => Self::Other(x),
+        }
+    }
+}
+
+impl From<BatteryStatus> for u8 {
+    fn from(value: BatteryStatus) -> Self {
+        match value {
+            BatteryStatus::Draining => 0,
+            BatteryStatus::Charging => 2,
+            BatteryStatus::CheckingCharge => 3,
+            BatteryStatus::Other(x) => x,
+        }
+    }
+}
+
+impl fmt::Display for BatteryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Draining => write!(f, "draining"),
+            Self::Charging => write!(f, "charging"),
+            Self::CheckingCharge => write!(f, "checking charge"),
+            Self::Other(x) => write!(f, "unknown ({})", x),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatterySettingsGet {
+    pub energy_saving: EnergySaving,
+    /// Run from mains without charging the battery
+    #[serde(with = "u8_bool_handler")]
+    pub bypass: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EnergySaving {
+    #[serde(with = "u8_bool_handler")]
+    pub enable: bool,
+    /// Charge in percent below which energy saving kicks in
+    pub minimum_battery_level: f32,
+    #[serde(with = "u8_bool_handler")]
+    pub disable_wifi: bool,
+    pub adjust_brightness: AdjustBrightness,
+}
+
+/// Brightness cap applied while energy saving
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjustBrightness {
+    #[serde(with = "u8_bool_handler")]
+    pub enable: bool,
     /// Range: 0 - 100
+    pub brightness: f32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct BatterySettingsPut {
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub brightness: Option<u8>,
-    /// Range: 2900 - 7000 (increments of 50)
+    pub energy_saving: Option<EnergySavingPut>,
     #[serde(
+        default,
         skip_serializing_if = "Option::is_none",
-        with = "temperature_option_handler"
+        with = "u8_bool_option_handler"
     )]
-    pub temperature: Option<u16>,
+    pub bypass: Option<bool>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
-pub struct AutoModePut {
+pub struct EnergySavingPut {
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "u8_bool_option_handler"
+    )]
+    pub enable: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub target_lux_value: Option<u16>,
+    pub minimum_battery_level: Option<f32>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "u8_bool_option_handler"
+    )]
+    pub disable_wifi: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adjust_brightness: Option<AdjustBrightnessPut>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjustBrightnessPut {
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "u8_bool_option_handler"
+    )]
+    pub enable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<f32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AccessoryInfoGet {
     pub product_name: String,
@@ -168,7 +463,7 @@ pub struct AccessoryInfoPut {
     pub display_name: Option<String>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct WifiInfo {
     pub ssid: String,
@@ -177,7 +472,7 @@ pub struct WifiInfo {
     pub rssi: i8,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct BtInfo {
     pub broadcast_mode: u8,
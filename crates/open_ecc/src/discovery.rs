@@ -0,0 +1,240 @@
+use crate::{contracts::AccessoryInfoGet, ecc::Ecc, error::Result, identity::Identity};
+use futures::{Stream, StreamExt};
+use mdns_sd::{IfKind, ServiceDaemon, ServiceEvent, ServiceInfo};
+use std::{
+    collections::HashSet,
+    future::ready,
+    net::{IpAddr, SocketAddr},
+    pin::pin,
+    time::Duration,
+};
+
+/// The service type advertised by Elgato lights
+pub const SERVICE_TYPE: &str = "_elg._tcp.local.";
+
+#[derive(Debug, Clone)]
+pub struct DiscoveredLight {
+    /// Host name advertised over mDNS
+    pub hostname: String,
+    pub address: IpAddr,
+    pub port: u16,
+    pub accessory_info: AccessoryInfoGet,
+}
+
+impl DiscoveredLight {
+    /// Endpoint usable with `Ecc` and `Light`, including the advertised port
+    pub fn endpoint(&self) -> String {
+        SocketAddr::new(self.address, self.port).to_string()
+    }
+}
+
+pub struct Discovery {
+    daemon: ServiceDaemon,
+}
+
+impl Discovery {
+    // Constructors
+
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            daemon: ServiceDaemon::new()?,
+        })
+    }
+
+    /// Browse on the loopback interfaces as well, which are disabled by default
+    pub fn with_loopback() -> Result<Self> {
+        let discovery = Self::new()?;
+        discovery
+            .daemon
+            .enable_interface(vec![IfKind::LoopbackV4, IfKind::LoopbackV6])?;
+        Ok(discovery)
+    }
+
+    // Public
+
+    /// Browse for lights, yielding each one once its accessory info has been fetched.
+    /// The stream never ends on its own.
+    pub fn browse<'a>(&'a self, ecc: &'a Ecc) -> Result<impl Stream<Item = DiscoveredLight> + 'a> {
+        let receiver = self.daemon.browse(SERVICE_TYPE)?;
+        let stream = receiver
+            .into_stream()
+            .filter_map(|event| async move {
+                match event {
+                    ServiceEvent::ServiceResolved(info) => Some(info),
+                    _ => None,
+                }
+            })
+            .filter_map(move |info| resolve(ecc, info));
+        Ok(stream)
+    }
+
+    /// Browse for lights until the timeout elapses, one entry per serial number
+    pub async fn discover(&self, ecc: &Ecc, timeout: Duration) -> Result<Vec<DiscoveredLight>> {
+        let mut serials = HashSet::new();
+        let lights = self
+            .browse(ecc)?
+            .take_until(tokio::time::sleep(timeout))
+            .filter(|light| ready(serials.insert(light.accessory_info.serial_number.clone())))
+            .collect()
+            .await;
+        Ok(lights)
+    }
+
+    /// Browse for the lights with the given identities, until all are found or the timeout elapses
+    pub async fn locate(
+        &self,
+        ecc: &Ecc,
+        identities: &[Identity],
+        timeout: Duration,
+    ) -> Result<Vec<DiscoveredLight>> {
+        let mut lights = pin!(self.browse(ecc)?.take_until(tokio::time::sleep(timeout)));
+        let mut found = Vec::<DiscoveredLight>::new();
+        while let Some(light) = lights.next().await {
+            let info = &light.accessory_info;
+            if identities.iter().any(|x| x.matches(info))
+                && !found
+                    .iter()
+                    .any(|x| x.accessory_info.serial_number == info.serial_number)
+            {
+                found.push(light);
+            }
+            if identities
+                .iter()
+                .all(|x| found.iter().any(|light| x.matches(&light.accessory_info)))
+            {
+                break;
+            }
+        }
+        Ok(found)
+    }
+}
+
+impl Drop for Discovery {
+    fn drop(&mut self) {
+        _ = self.daemon.shutdown();
+    }
+}
+
+async fn resolve(ecc: &Ecc, info: ServiceInfo) -> Option<DiscoveredLight> {
+    let port = info.get_port();
+    let mut addresses = info.get_addresses().iter().copied().collect::<Vec<_>>();
+    addresses.sort_by_key(|address| address.is_ipv6());
+
+    for address in addresses {
+        let endpoint = SocketAddr::new(address, port).to_string();
+        if let Ok(accessory_info) = ecc.accessory_info_get(&endpoint).await {
+            return Some(DiscoveredLight {
+                hostname: info.get_hostname().trim_end_matches('.').to_string(),
+                address,
+                port,
+                accessory_info,
+            });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    async fn serve_accessory_info(listener: TcpListener, info: AccessoryInfoGet) {
+        let body = serde_json::to_string(&info).unwrap();
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                return;
+            };
+            let mut buf = [0; 1024];
+            _ = stream.read(&mut buf).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            _ = stream.write_all(response.as_bytes()).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_discover_loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let accessory_info = AccessoryInfoGet {
+            product_name: "Elgato Key Light".to_string(),
+            serial_number: "BW00000000".to_string(),
+            ..Default::default()
+        };
+        tokio::spawn(serve_accessory_info(listener, accessory_info));
+
+        let responder = Discovery::with_loopback().unwrap();
+        let service = ServiceInfo::new(
+            SERVICE_TYPE,
+            "Elgato Key Light 0000",
+            "elgato-key-light-0000.local.",
+            "127.0.0.1",
+            port,
+            None,
+        )
+        .unwrap();
+        responder.daemon.register(service).unwrap();
+
+        let ecc = Ecc::default();
+        let discovery = Discovery::with_loopback().unwrap();
+        let lights = discovery
+            .discover(&ecc, Duration::from_secs(3))
+            .await
+            .unwrap();
+
+        let light = lights
+            .iter()
+            .find(|x| x.port == port)
+            .expect("Light was not discovered");
+        assert_eq!(light.accessory_info.serial_number, "BW00000000");
+        assert_eq!(light.hostname, "elgato-key-light-0000.local");
+        assert_eq!(light.endpoint(), format!("127.0.0.1:{}", port));
+    }
+
+    #[tokio::test]
+    async fn test_locate_loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let accessory_info = AccessoryInfoGet {
+            product_name: "Elgato Key Light".to_string(),
+            serial_number: "BW00000001".to_string(),
+            mac_address: "3C:6A:9D:00:00:01".to_string(),
+            ..Default::default()
+        };
+        tokio::spawn(serve_accessory_info(listener, accessory_info));
+
+        let responder = Discovery::with_loopback().unwrap();
+        let service = ServiceInfo::new(
+            SERVICE_TYPE,
+            "Elgato Key Light 0001",
+            "elgato-key-light-0001.local.",
+            "127.0.0.1",
+            port,
+            None,
+        )
+        .unwrap();
+        responder.daemon.register(service).unwrap();
+
+        let ecc = Ecc::default();
+        let discovery = Discovery::with_loopback().unwrap();
+        let identity = Identity {
+            mac_address: "3c6a9d000001".to_string(),
+            ..Default::default()
+        };
+        let lights = discovery
+            .locate(&ecc, &[identity], Duration::from_secs(3))
+            .await
+            .unwrap();
+
+        assert_eq!(lights.len(), 1);
+        assert_eq!(lights[0].endpoint(), format!("127.0.0.1:{}", port));
+    }
+}
@@ -1,44 +1,135 @@
 use crate::{
     contracts::{
-        AccessoryInfoGet, AccessoryInfoPut, LightsGet, LightsPut, LightsSettingsGet,
-        LightsSettingsPut, WifiConfig,
+        AccessoryInfoGet, AccessoryInfoPut, BatteryInfo, BatterySettingsGet, BatterySettingsPut,
+        LightsGet, LightsPut, LightsSettingsGet, LightsSettingsPut, WifiConfig,
     },
+    error::Result,
     helpers::encrypt_wifi_payload,
-    serialization::deser_response,
+    serialization::{check_response, deser_response},
 };
-use anyhow::Result;
 use reqwest::{
-    Client,
+    Client, Method, RequestBuilder,
     header::{CONTENT_TYPE, HeaderValue},
 };
+use std::{net::SocketAddr, time::Duration};
+
+pub const DEFAULT_PORT: u16 = 9123;
+pub const DEFAULT_NAMESPACE: &str = "/elgato";
+pub const DEFAULT_USER_AGENT: &str = concat!("open_ecc/", env!("CARGO_PKG_VERSION"));
 
 pub struct Ecc {
     client: Client,
     protocol: &'static str,
     port: u16,
     namespace: String,
+    timeout: Option<Duration>,
 }
 
 impl Default for Ecc {
+    fn default() -> Self {
+        EccBuilder::default()
+            .build()
+            .expect("Failed to build HTTP client")
+    }
+}
+
+pub struct EccBuilder {
+    client: Option<Client>,
+    port: u16,
+    namespace: String,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: String,
+}
+
+impl Default for EccBuilder {
     fn default() -> Self {
         Self {
-            client: Client::new(),
-            protocol: "http",
-            port: 9123,
-            namespace: "/elgato".to_string(),
+            client: None,
+            port: DEFAULT_PORT,
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            timeout: None,
+            connect_timeout: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
         }
     }
 }
 
+impl EccBuilder {
+    /// Port used for endpoints that don't specify one
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Path prefix of the API, `/elgato` on real devices
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Timeout for each request, from connecting until the response body is read
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for establishing a connection.
+    /// Ignored when a custom client is provided.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// User agent sent with each request.
+    /// Ignored when a custom client is provided.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Use a preconfigured client instead of building one
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn build(self) -> Result<Ecc> {
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let mut builder = Client::builder().user_agent(self.user_agent);
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                builder.build()?
+            }
+        };
+        Ok(Ecc {
+            client,
+            protocol: "http",
+            port: self.port,
+            namespace: self.namespace,
+            timeout: self.timeout,
+        })
+    }
+}
+
 impl Ecc {
+    // Constructors
+
+    pub fn builder() -> EccBuilder {
+        EccBuilder::default()
+    }
+
     // Public
 
     pub async fn wifi_config(&self, endpoint: &str, payload: &WifiConfig) -> Result<()> {
         let accessory_info = self.accessory_info_get(endpoint).await?;
         let encrypted_bytes = encrypt_wifi_payload(&accessory_info, payload)?;
         let url = format!("{}/wifi-info", self.format_url(endpoint));
-        self.client
-            .put(&url)
+        let response = self
+            .request(Method::PUT, &url)
             .header(
                 CONTENT_TYPE,
                 HeaderValue::from_static("application/octet-stream"),
@@ -46,32 +137,32 @@ impl Ecc {
             .body(encrypted_bytes)
             .send()
             .await?;
-        Ok(())
+        check_response(response).await
     }
 
     pub async fn identify(&self, endpoint: &str) -> Result<()> {
         let url = format!("{}/identify", self.format_url(endpoint));
-        self.client.post(&url).send().await?;
-        Ok(())
+        let response = self.request(Method::POST, &url).send().await?;
+        check_response(response).await
     }
 
     pub async fn lights_get(&self, endpoint: &str) -> Result<LightsGet> {
         let url = format!("{}/lights", self.format_url(endpoint));
-        let response = self.client.get(&url).send().await?;
+        let response = self.request(Method::GET, &url).send().await?;
         let result = deser_response::<LightsGet>(response).await?;
         Ok(result)
     }
 
     pub async fn lights_put(&self, endpoint: &str, payload: &LightsPut) -> Result<LightsGet> {
         let url = format!("{}/lights", self.format_url(endpoint));
-        let response = self.client.put(&url).json(payload).send().await?;
+        let response = self.request(Method::PUT, &url).json(payload).send().await?;
         let result = deser_response::<LightsGet>(response).await?;
         Ok(result)
     }
 
     pub async fn lights_settings_get(&self, endpoint: &str) -> Result<LightsSettingsGet> {
         let url = format!("{}/lights/settings", self.format_url(endpoint));
-        let response = self.client.get(&url).send().await?;
+        let response = self.request(Method::GET, &url).send().await?;
         let result = deser_response::<LightsSettingsGet>(response).await?;
         Ok(result)
     }
@@ -81,14 +172,14 @@ impl Ecc {
         endpoint: &str,
         payload: &LightsSettingsPut,
     ) -> Result<()> {
-        let url = format!("{}/lights", self.format_url(endpoint));
-        self.client.put(&url).json(payload).send().await?;
-        Ok(())
+        let url = format!("{}/lights/settings", self.format_url(endpoint));
+        let response = self.request(Method::PUT, &url).json(payload).send().await?;
+        check_response(response).await
     }
 
     pub async fn accessory_info_get(&self, endpoint: &str) -> Result<AccessoryInfoGet> {
         let url = format!("{}/accessory-info", self.format_url(endpoint));
-        let response = self.client.get(&url).send().await?;
+        let response = self.request(Method::GET, &url).send().await?;
         let result = deser_response::<AccessoryInfoGet>(response).await?;
         Ok(result)
     }
@@ -99,16 +190,100 @@ impl Ecc {
         payload: &AccessoryInfoPut,
     ) -> Result<()> {
         let url = format!("{}/accessory-info", self.format_url(endpoint));
-        self.client.put(&url).json(payload).send().await?;
-        Ok(())
+        let response = self.request(Method::PUT, &url).json(payload).send().await?;
+        check_response(response).await
+    }
+
+    /// Only on lights with a battery, such as the Key Light Mini
+    pub async fn battery_info_get(&self, endpoint: &str) -> Result<BatteryInfo> {
+        let url = format!("{}/battery-info", self.format_url(endpoint));
+        let response = self.request(Method::GET, &url).send().await?;
+        let result = deser_response::<BatteryInfo>(response).await?;
+        Ok(result)
+    }
+
+    /// Only on lights with a battery, such as the Key Light Mini
+    pub async fn battery_settings_get(&self, endpoint: &str) -> Result<BatterySettingsGet> {
+        let url = format!("{}/battery-settings", self.format_url(endpoint));
+        let response = self.request(Method::GET, &url).send().await?;
+        let result = deser_response::<BatterySettingsGet>(response).await?;
+        Ok(result)
+    }
+
+    /// Only on lights with a battery, such as the Key Light Mini
+    pub async fn battery_settings_put(
+        &self,
+        endpoint: &str,
+        payload: &BatterySettingsPut,
+    ) -> Result<()> {
+        let url = format!("{}/battery-settings", self.format_url(endpoint));
+        let response = self.request(Method::PUT, &url).json(payload).send().await?;
+        check_response(response).await
     }
 
     // Private
 
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match self.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        }
+    }
+
     fn format_url(&self, endpoint: &str) -> String {
-        format!(
-            "{}://{}:{}{}",
-            self.protocol, endpoint, self.port, self.namespace
-        )
+        if has_port(endpoint) {
+            format!("{}://{}{}", self.protocol, endpoint, self.namespace)
+        } else {
+            format!(
+                "{}://{}:{}{}",
+                self.protocol, endpoint, self.port, self.namespace
+            )
+        }
+    }
+}
+
+/// Whether the endpoint already carries a port, e.g. `192.168.0.50:9123` or `[fe80::1]:9123`
+fn has_port(endpoint: &str) -> bool {
+    endpoint.parse::<SocketAddr>().is_ok()
+        || endpoint
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.contains(':') && port.parse::<u16>().is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_url() {
+        let ecc = Ecc::default();
+        assert_eq!(
+            ecc.format_url("192.168.0.50"),
+            "http://192.168.0.50:9123/elgato"
+        );
+        assert_eq!(
+            ecc.format_url("192.168.0.50:9124"),
+            "http://192.168.0.50:9124/elgato"
+        );
+        assert_eq!(
+            ecc.format_url("key-light.local"),
+            "http://key-light.local:9123/elgato"
+        );
+        assert_eq!(ecc.format_url("[::1]:9124"), "http://[::1]:9124/elgato");
+    }
+
+    #[test]
+    fn test_builder_format_url() {
+        let ecc = Ecc::builder()
+            .port(8080)
+            .namespace("/fake")
+            .build()
+            .unwrap();
+        assert_eq!(ecc.format_url("127.0.0.1"), "http://127.0.0.1:8080/fake");
+        assert_eq!(
+            ecc.format_url("127.0.0.1:9123"),
+            "http://127.0.0.1:9123/fake"
+        );
     }
 }
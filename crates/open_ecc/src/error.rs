@@ -0,0 +1,74 @@
+use crate::{contracts::JsonError, identity::Identity};
+use reqwest::StatusCode;
+use thiserror::Error;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The device did not respond in time
+    #[error("Request timed out")]
+    Timeout(#[source] reqwest::Error),
+    /// The device could not be reached
+    #[error("Request failed")]
+    Transport(#[source] reqwest::Error),
+    /// The device rejected the request
+    #[error("Device responded with {status}{}", format_errors(errors))]
+    Status {
+        status: StatusCode,
+        errors: Vec<JsonError>,
+    },
+    /// The device responded with an unexpected body
+    #[error("Failed to deserialize response")]
+    Deserialize(#[from] serde_json::Error),
+    /// The device reported no lights
+    #[error("No lights found")]
+    NoLights,
+    /// The device has fewer lights than the requested index
+    #[error("No light at index {index}, the device has {count} light(s)")]
+    NoLightAtIndex { index: usize, count: usize },
+    /// The light does not support or report the feature
+    #[error("The light does not support {0}")]
+    Unsupported(&'static str),
+    /// A value was rejected before being sent to the device
+    #[error("Invalid value: {0}")]
+    Validation(String),
+    /// Another device answered at the address of a known one
+    #[error("Found device {found} instead of {expected}")]
+    WrongDevice { expected: Identity, found: Identity },
+    /// Browsing for devices failed
+    #[error("Discovery failed")]
+    Discovery(#[from] mdns_sd::Error),
+}
+
+impl Error {
+    /// Whether the device could not be reached or did not respond in time
+    pub fn is_offline(&self) -> bool {
+        matches!(self, Self::Timeout(_) | Self::Transport(_))
+    }
+
+    /// Error codes reported by the device, if any
+    pub fn codes(&self) -> Vec<i32> {
+        match self {
+            Self::Status { errors, .. } => errors.iter().map(|x| x.code).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Self::Timeout(error)
+        } else {
+            Self::Transport(error)
+        }
+    }
+}
+
+fn format_errors(errors: &[JsonError]) -> String {
+    errors
+        .iter()
+        .map(|x| format!(": {} (code {})", x.message, x.code))
+        .collect()
+}
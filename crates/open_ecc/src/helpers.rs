@@ -1,12 +1,16 @@
-use crate::contracts::{AccessoryInfoGet, WifiConfig};
-use anyhow::Result;
+use crate::{
+    contracts::{AccessoryInfoGet, WifiConfig},
+    error::{Error, Result},
+};
 use cipher::{BlockEncryptMut, KeyIvInit, block_padding::NoPadding};
 use rand::Rng;
 
 type Aes128Cbc = cbc::Encryptor<aes::Aes128>;
 
+const WIFI_IV: &str = "049F6F1149C6F84B1B14913C71E9CDBE";
+
 fn add_padding(bytes: &mut Vec<u8>) {
-    while bytes.len() % 16 != 0 {
+    while !bytes.len().is_multiple_of(16) {
         bytes.push(0);
     }
 }
@@ -28,12 +32,21 @@ pub fn encrypt_wifi_payload(
     accessory_info: &AccessoryInfoGet,
     payload: &WifiConfig,
 ) -> Result<Vec<u8>> {
+    if let Some(channel) = payload.channel
+        && !(1..=14).contains(&channel)
+    {
+        return Err(Error::Validation(format!(
+            "WiFi channel {} is outside of 1 - 14",
+            channel
+        )));
+    }
+
     let mut bytes_array = serde_json::to_vec_pretty(payload).expect("Failed to serialize JSON");
     add_padding(&mut bytes_array);
     let random_array = random_prefix();
     let mut data: Vec<u8> = [random_array, bytes_array].concat();
 
-    let iv_bytes = hex::decode("049F6F1149C6F84B1B14913C71E9CDBE").expect("Invalid IV hex");
+    let iv_bytes = hex::decode(WIFI_IV).expect("Invalid IV hex");
 
     let key = get_encryption_key(accessory_info);
 
@@ -49,6 +62,7 @@ pub fn encrypt_wifi_payload(
     Ok(encrypted_bytes.to_vec())
 }
 
+/// Temperature in Kelvin of an API value, the same on every model
 pub fn api_to_kelvin(api: u16) -> u16 {
     const API_MIN: u16 = 143;
     const API_MAX: u16 = 344;
@@ -64,6 +78,7 @@ pub fn api_to_kelvin(api: u16) -> u16 {
     stepped.clamp(K_MIN as f64, K_MAX as f64) as u16
 }
 
+/// API value of a temperature in Kelvin, the same on every model
 pub fn kelvin_to_api(kelvin: u16) -> u16 {
     const API_MIN: u16 = 143;
     const API_MAX: u16 = 344;
@@ -103,7 +118,7 @@ mod tests {
         assert_eq!(api_to_kelvin(143), 2900);
         assert_eq!(api_to_kelvin(344), 7000);
         let mid_kelvin = api_to_kelvin((143 + 344) / 2);
-        assert!(mid_kelvin % 50 == 0);
+        assert!(mid_kelvin.is_multiple_of(50));
     }
 
     #[test]
@@ -0,0 +1,112 @@
+use crate::{
+    contracts::AccessoryInfoGet,
+    error::{Error, Result},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What tells a device apart when its address changes
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Identity {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub serial_number: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub mac_address: String,
+}
+
+impl Identity {
+    // Constructors
+
+    pub fn from_accessory_info(info: &AccessoryInfoGet) -> Self {
+        Self {
+            serial_number: info.serial_number.clone(),
+            mac_address: info.mac_address.clone(),
+        }
+    }
+
+    // Public
+
+    /// Neither a serial number nor a MAC address is known
+    pub fn is_empty(&self) -> bool {
+        self.serial_number.is_empty() && self.mac_address.is_empty()
+    }
+
+    /// Whether `info` is this device, by serial number or MAC address
+    pub fn matches(&self, info: &AccessoryInfoGet) -> bool {
+        (!self.serial_number.is_empty() && self.serial_number == info.serial_number)
+            || same_mac(&self.mac_address, &info.mac_address)
+    }
+
+    /// `Error::WrongDevice` unless `info` is this device
+    pub fn verify(&self, info: &AccessoryInfoGet) -> Result<()> {
+        match self.matches(info) {
+            true => Ok(()),
+            false => Err(Error::WrongDevice {
+                expected: self.clone(),
+                found: Self::from(info),
+            }),
+        }
+    }
+
+    /// Whether `id` is the serial number or MAC address of this device
+    pub fn is(&self, id: &str) -> bool {
+        (!self.serial_number.is_empty() && self.serial_number.eq_ignore_ascii_case(id))
+            || same_mac(&self.mac_address, id)
+    }
+}
+
+impl From<&AccessoryInfoGet> for Identity {
+    fn from(info: &AccessoryInfoGet) -> Self {
+        Self::from_accessory_info(info)
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.serial_number.is_empty() {
+            true => write!(f, "{}", self.mac_address),
+            false => write!(f, "{}", self.serial_number),
+        }
+    }
+}
+
+/// MAC addresses compared without separators or case
+fn same_mac(a: &str, b: &str) -> bool {
+    let normalize = |x: &str| {
+        x.chars()
+            .filter(char::is_ascii_hexdigit)
+            .map(|x| x.to_ascii_uppercase())
+            .collect::<String>()
+    };
+    let a = normalize(a);
+    !a.is_empty() && a == normalize(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let identity = Identity {
+            serial_number: "BW001".to_string(),
+            mac_address: "3C:6A:9D:00:00:01".to_string(),
+        };
+        let info = |serial_number: &str, mac_address: &str| AccessoryInfoGet {
+            serial_number: serial_number.to_string(),
+            mac_address: mac_address.to_string(),
+            ..Default::default()
+        };
+
+        assert!(identity.matches(&info("BW001", "")));
+        assert!(identity.matches(&info("", "3c-6a-9d-00-00-01")));
+        assert!(!identity.matches(&info("BW002", "3C:6A:9D:00:00:02")));
+        assert!(!Identity::default().matches(&info("", "")));
+        assert!(identity.is("bw001") && identity.is("3c6a9d000001"));
+        assert!(matches!(
+            identity.verify(&info("BW002", "")),
+            Err(Error::WrongDevice { .. })
+        ));
+    }
+}
@@ -1,5 +1,20 @@
+pub mod adjust;
+pub mod backup;
+pub mod capabilities;
+pub mod catalogue;
+pub mod color;
 pub mod contracts;
+pub mod discovery;
 pub mod ecc;
+pub mod error;
 pub(crate) mod helpers;
+pub mod identity;
 pub mod light;
+pub mod scan;
 pub(crate) mod serialization;
+pub mod snapshot;
+pub mod transition;
+pub mod units;
+pub mod watch;
+
+pub use error::{Error, Result};
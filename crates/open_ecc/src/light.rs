@@ -1,23 +1,101 @@
 use crate::{
-    contracts::{LightGet, LightPut, LightsGet, LightsPut},
+    adjust::{BrightnessValue, TemperatureValue},
+    backup::DeviceBackup,
+    capabilities::{Capabilities, Feature},
+    color::Hsv,
+    contracts::{
+        AccessoryInfoGet, AccessoryInfoPut, AdjustBrightnessPut, AutoModePut, BatteryInfo,
+        BatterySettingsGet, BatterySettingsPut, EnergySavingPut, Favourite, LightGet, LightPut,
+        LightsGet, LightsPut, LightsSettingsGet, LightsSettingsPut, PowerOnBehavior,
+        RemoteControlPut, Scene,
+    },
     ecc::Ecc,
+    error::{Error, Result},
+    identity::Identity,
+    snapshot::DeviceSnapshot,
+    transition::{Transition, TransitionOutcome},
+    units::{ApiTemperature, Brightness, Kelvin},
+    watch::{LightEvent, Poll},
 };
-use anyhow::{Result, anyhow};
+use futures::{Stream, StreamExt, stream};
+use std::time::Duration;
+use tokio::time::{Instant, MissedTickBehavior, sleep_until};
+use tokio_util::sync::CancellationToken;
 
+/// Number of lights a group talks to at once, unless configured otherwise
+pub const DEFAULT_CONCURRENCY: usize = 16;
+
+#[derive(Clone, Copy)]
 pub struct Light<'a> {
     ecc: &'a Ecc,
     endpoint: &'a str,
+    index: Option<usize>,
+    capabilities: Option<Capabilities>,
+    identity: Option<&'a Identity>,
 }
 
 impl<'a> Light<'a> {
     // Construtors
 
+    /// Control every light on the device
     pub fn new(ecc: &'a Ecc, endpoint: &'a str) -> Self {
-        Self { ecc, endpoint }
+        Self {
+            ecc,
+            endpoint,
+            index: None,
+            capabilities: None,
+            identity: None,
+        }
+    }
+
+    /// Control only the light at `index` on a device with multiple lights
+    pub fn channel(&self, index: usize) -> Self {
+        Self {
+            index: Some(index),
+            ..*self
+        }
+    }
+
+    /// Use known capabilities instead of reading them from the device when needed
+    pub fn with_capabilities(&self, capabilities: Capabilities) -> Self {
+        Self {
+            capabilities: Some(capabilities),
+            ..*self
+        }
+    }
+
+    /// Expect the device with `identity`, failing with `Error::WrongDevice`
+    /// when the accessory info read by an operation is that of another device
+    pub fn with_identity(&self, identity: &'a Identity) -> Self {
+        Self {
+            identity: (!identity.is_empty()).then_some(identity),
+            ..*self
+        }
     }
 
     // Public
 
+    pub fn endpoint(&self) -> &'a str {
+        self.endpoint
+    }
+
+    /// Index of the light being controlled, `None` for all of them
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// What the device supports, read from the device unless given with `with_capabilities`
+    pub async fn capabilities(&self) -> Result<Capabilities> {
+        if let Some(capabilities) = self.capabilities {
+            return Ok(capabilities);
+        }
+        let (accessory_info, lights) = futures::try_join!(
+            self.accessory_info_get(),
+            self.ecc.lights_get(self.endpoint),
+        )?;
+        Ok(Capabilities::from_accessory_info(&accessory_info).lights(lights.lights.len()))
+    }
+
     /// Turn on the light
     pub async fn on(&self) -> Result<()> {
         self.set_light(|_| LightPut {
@@ -48,6 +126,11 @@ impl<'a> Light<'a> {
         Ok(())
     }
 
+    /// Get the state of every light on the device
+    pub async fn lights_get(&self) -> Result<LightsGet> {
+        self.ecc.lights_get(self.endpoint).await
+    }
+
     /// Get light state
     pub async fn state_get(&self) -> Result<bool> {
         self.field_get(|x| x.on).await
@@ -63,13 +146,21 @@ impl<'a> Light<'a> {
         Ok(())
     }
 
-    /// Get temperature in Kelvin [2900..=7000]
-    pub async fn temperature_get(&self) -> Result<u16> {
-        self.field_get(|x| x.temperature).await
+    /// Get temperature in Kelvin, rounded to steps of 50K
+    pub async fn temperature_get(&self) -> Result<Kelvin> {
+        self.field_get(|x| x.temperature.map(Kelvin::from))
+            .await?
+            .ok_or(Error::Unsupported("temperature"))
     }
 
-    /// Set temperature in Kelvin [2900..=7000]
-    pub async fn temperature_set(&self, value: u16) -> Result<LightsGet> {
+    /// Set temperature from `Kelvin` or `Mired`, on lights with a white mode
+    pub async fn temperature_set(&self, value: impl Into<Kelvin>) -> Result<LightsGet> {
+        self.require(Feature::Temperature).await?;
+        self.api_temperature_set(value.into().into()).await
+    }
+
+    /// Set the exact temperature value sent to the device
+    pub async fn api_temperature_set(&self, value: ApiTemperature) -> Result<LightsGet> {
         self.set_light(|_| LightPut {
             temperature: Some(value),
             ..Default::default()
@@ -77,13 +168,26 @@ impl<'a> Light<'a> {
         .await
     }
 
-    /// Get brightness [0..=100]
-    pub async fn brightness_get(&self) -> Result<u8> {
+    /// Set temperature to a value resolved against the current temperature of each light.
+    /// Lights showing a colour are left as they are by relative values.
+    pub async fn temperature_adjust(&self, value: TemperatureValue) -> Result<LightsGet> {
+        self.require(Feature::Temperature).await?;
+        self.set_light(|x| LightPut {
+            temperature: value
+                .resolve(x.temperature.map(Kelvin::from))
+                .map(ApiTemperature::from),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Get brightness
+    pub async fn brightness_get(&self) -> Result<Brightness> {
         self.field_get(|x| x.brightness).await
     }
 
-    /// Set brightness [0..=100]
-    pub async fn brightness_set(&self, value: u8) -> Result<LightsGet> {
+    /// Set brightness
+    pub async fn brightness_set(&self, value: Brightness) -> Result<LightsGet> {
         self.set_light(|_| LightPut {
             brightness: Some(value),
             ..Default::default()
@@ -91,20 +195,452 @@ impl<'a> Light<'a> {
         .await
     }
 
+    /// Set brightness to a value resolved against the current brightness of each light
+    pub async fn brightness_adjust(&self, value: BrightnessValue) -> Result<LightsGet> {
+        self.set_light(|x| LightPut {
+            brightness: Some(value.resolve(x.brightness)),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Get colour, on lights that support it
+    pub async fn color_get(&self) -> Result<Hsv> {
+        self.field_get(|x| match (x.hue, x.saturation) {
+            (Some(hue), Some(saturation)) => Ok(Hsv {
+                hue,
+                saturation,
+                value: x.brightness.get() as f32,
+            }),
+            _ => Err(Error::Unsupported("colour")),
+        })
+        .await?
+    }
+
+    /// Set colour, with the value of the colour as brightness
+    pub async fn color_set(&self, color: Hsv) -> Result<LightsGet> {
+        let color = Hsv::new(color.hue, color.saturation, color.value)?;
+        self.require(Feature::Color).await?;
+        self.set_light(|_| LightPut {
+            hue: Some(color.hue),
+            saturation: Some(color.saturation),
+            brightness: Some(Brightness::clamped(color.value.round() as i32)),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Upload and start an animated scene, on lights that support it
+    pub async fn scene_set(&self, scene: &Scene) -> Result<LightsGet> {
+        if scene.frames.is_empty() {
+            return Err(Error::Validation("Scene has no frames".to_string()));
+        }
+        for (i, frame) in scene.frames.iter().enumerate() {
+            if frame.segments.is_empty() {
+                return Err(Error::Validation(format!(
+                    "Scene frame {} has no segments",
+                    i
+                )));
+            }
+            for segment in &frame.segments {
+                Hsv::new(segment.hue, segment.saturation, segment.brightness)?;
+            }
+        }
+        self.require(Feature::Scenes).await?;
+        let scene = Scene {
+            number_of_scene_elements: scene.frames.len() as u16,
+            ..scene.clone()
+        };
+        self.set_light(|_| LightPut {
+            on: Some(true),
+            scene: Some(scene.clone()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Fade brightness and/or temperature, stopping early when `cancel` is triggered
+    pub async fn transition(
+        &self,
+        transition: &Transition,
+        cancel: &CancellationToken,
+    ) -> Result<TransitionOutcome> {
+        transition.validate()?;
+        if transition.temperature.is_some() {
+            self.require(Feature::Temperature).await?;
+        }
+        let start = self.ecc.lights_get(self.endpoint).await?;
+        let started = Instant::now();
+        let mut last = None;
+
+        for step in 1..=transition.steps() {
+            tokio::select! {
+                _ = cancel.cancelled() => return Ok(TransitionOutcome::Cancelled),
+                _ = sleep_until(started + transition.interval * step) => {}
+            }
+            let values = start
+                .lights
+                .iter()
+                .map(|x| transition.at(x, step))
+                .collect::<Vec<_>>();
+            if last.as_ref() == Some(&values) {
+                continue;
+            }
+            self.put_lights(&start, |i, _| {
+                let (brightness, temperature) = values[i];
+                LightPut {
+                    brightness,
+                    temperature: temperature.map(ApiTemperature::from),
+                    ..Default::default()
+                }
+            })
+            .await?;
+            last = Some(values);
+        }
+
+        Ok(TransitionOutcome::Completed)
+    }
+
+    /// Poll the device every `interval`, yielding what changed since the previous poll.
+    /// Failed polls yield an error and the stream carries on, it never ends on its own.
+    pub fn watch(&self, interval: Duration) -> impl Stream<Item = Result<LightEvent>> + 'a {
+        let light = *self;
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        stream::unfold(
+            (ticker, None::<Poll>),
+            move |(mut ticker, last)| async move {
+                ticker.tick().await;
+                let (events, last) = match light.poll().await {
+                    Ok(next) => {
+                        let events = match &last {
+                            Some(last) => last.changes(&next, light.index),
+                            None => Vec::new(),
+                        };
+                        (events.into_iter().map(Ok).collect(), Some(next))
+                    }
+                    Err(e) => (vec![Err(e)], last),
+                };
+                Some((stream::iter(events), (ticker, last)))
+            },
+        )
+        .flatten()
+    }
+
+    /// Get the settings of the device, shared by all of its lights
+    pub async fn settings_get(&self) -> Result<LightsSettingsGet> {
+        self.ecc.lights_settings_get(self.endpoint).await
+    }
+
+    /// Update the settings of the device, fields left as `None` are unchanged
+    pub async fn settings_set(&self, settings: &LightsSettingsPut) -> Result<()> {
+        self.ecc.lights_settings_put(self.endpoint, settings).await
+    }
+
+    /// Set whether the light turns on when it gets power
+    pub async fn power_on_behavior_set(&self, value: PowerOnBehavior) -> Result<()> {
+        self.settings_set(&LightsSettingsPut {
+            power_on_behavior: Some(value),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Set the brightness used when the light turns on after getting power
+    pub async fn power_on_brightness_set(&self, value: Brightness) -> Result<()> {
+        self.settings_set(&LightsSettingsPut {
+            power_on_brightness: Some(value),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Set the temperature used when the light turns on after getting power,
+    /// on lights with a white mode
+    pub async fn power_on_temperature_set(&self, value: impl Into<Kelvin>) -> Result<()> {
+        self.require(Feature::Temperature).await?;
+        self.settings_set(&LightsSettingsPut {
+            power_on_temperature: Some(value.into().into()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Set how long the light takes to fade in when turned on
+    pub async fn switch_on_duration_set(&self, value: Duration) -> Result<()> {
+        self.settings_set(&LightsSettingsPut {
+            switch_on_duration_ms: Some(duration_ms(value)?),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Set how long the light takes to fade out when turned off
+    pub async fn switch_off_duration_set(&self, value: Duration) -> Result<()> {
+        self.settings_set(&LightsSettingsPut {
+            switch_off_duration_ms: Some(duration_ms(value)?),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Set how long the light takes to change brightness, temperature or colour
+    pub async fn color_change_duration_set(&self, value: Duration) -> Result<()> {
+        self.settings_set(&LightsSettingsPut {
+            color_change_duration_ms: Some(duration_ms(value)?),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Get the favourites stored for the remote control
+    pub async fn favourites_get(&self) -> Result<Vec<Favourite>> {
+        Ok(self.settings_get().await?.remote_control.favourites)
+    }
+
+    /// Replace the favourites stored for the remote control
+    pub async fn favourites_set(&self, favourites: &[Favourite]) -> Result<()> {
+        self.settings_set(&LightsSettingsPut {
+            remote_control: Some(RemoteControlPut {
+                favourites: Some(favourites.iter().copied().map(Into::into).collect()),
+                auto_mode: None,
+            }),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Append a favourite, returning the favourites as stored afterwards
+    pub async fn favourites_add(&self, favourite: Favourite) -> Result<Vec<Favourite>> {
+        let mut favourites = self.favourites_get().await?;
+        favourites.push(favourite);
+        self.favourites_set(&favourites).await?;
+        Ok(favourites)
+    }
+
+    /// Remove the favourite at `index`, returning the favourites as stored afterwards
+    pub async fn favourites_remove(&self, index: usize) -> Result<Vec<Favourite>> {
+        let mut favourites = self.favourites_get().await?;
+        if index >= favourites.len() {
+            return Err(Error::Validation(format!(
+                "No favourite at index {}, there are {}",
+                index,
+                favourites.len()
+            )));
+        }
+        favourites.remove(index);
+        self.favourites_set(&favourites).await?;
+        Ok(favourites)
+    }
+
+    /// Set the ambient light level in lux the remote control's auto mode aims for
+    pub async fn auto_mode_target_set(&self, lux: u16) -> Result<()> {
+        self.settings_set(&LightsSettingsPut {
+            remote_control: Some(RemoteControlPut {
+                favourites: None,
+                auto_mode: Some(AutoModePut {
+                    target_lux_value: Some(lux),
+                }),
+            }),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Get the charge level and power source, on lights with a battery
+    pub async fn battery_info_get(&self) -> Result<BatteryInfo> {
+        self.require(Feature::Battery).await?;
+        battery(self.ecc.battery_info_get(self.endpoint).await)
+    }
+
+    /// Get the energy saving settings, on lights with a battery
+    pub async fn battery_settings_get(&self) -> Result<BatterySettingsGet> {
+        self.require(Feature::Battery).await?;
+        battery(self.ecc.battery_settings_get(self.endpoint).await)
+    }
+
+    /// Update the energy saving settings, fields left as `None` are unchanged
+    pub async fn battery_settings_set(&self, settings: &BatterySettingsPut) -> Result<()> {
+        self.require(Feature::Battery).await?;
+        battery(self.ecc.battery_settings_put(self.endpoint, settings).await)
+    }
+
+    /// Turn energy saving on or off, on lights with a battery
+    pub async fn energy_saving_set(&self, enable: bool) -> Result<()> {
+        self.battery_settings_set(&BatterySettingsPut {
+            energy_saving: Some(EnergySavingPut {
+                enable: Some(enable),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Cap the brightness while energy saving, `None` removes the cap
+    pub async fn brightness_cap_set(&self, value: Option<Brightness>) -> Result<()> {
+        self.battery_settings_set(&BatterySettingsPut {
+            energy_saving: Some(EnergySavingPut {
+                adjust_brightness: Some(AdjustBrightnessPut {
+                    enable: Some(value.is_some()),
+                    brightness: value.map(|x| x.get() as f32),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Get the identity and network details of the device
+    pub async fn accessory_info_get(&self) -> Result<AccessoryInfoGet> {
+        let accessory_info = self.ecc.accessory_info_get(self.endpoint).await?;
+        if let Some(identity) = self.identity {
+            identity.verify(&accessory_info)?;
+        }
+        Ok(accessory_info)
+    }
+
+    /// Set the name shown in Control Center
+    pub async fn display_name_set(&self, name: &str) -> Result<()> {
+        self.ecc
+            .accessory_info_put(
+                self.endpoint,
+                &AccessoryInfoPut {
+                    display_name: Some(name.to_string()),
+                },
+            )
+            .await
+    }
+
+    /// Back up the identity, settings and state of the device
+    pub async fn backup(&self) -> Result<DeviceBackup> {
+        let (accessory_info, state) =
+            futures::try_join!(self.accessory_info_get(), self.capture(true))?;
+        Ok(DeviceBackup {
+            accessory_info,
+            state,
+        })
+    }
+
+    /// Restore the display name, settings and state of a backup
+    pub async fn restore_backup(&self, backup: &DeviceBackup) -> Result<()> {
+        self.display_name_set(&backup.accessory_info.display_name)
+            .await?;
+        self.restore(&backup.state, None).await
+    }
+
+    /// Capture the state of the device, and its settings if `settings` is set
+    pub async fn capture(&self, settings: bool) -> Result<DeviceSnapshot> {
+        let lights = self.ecc.lights_get(self.endpoint).await?;
+        let settings = match settings {
+            true => Some(self.settings_get().await?),
+            false => None,
+        };
+        Ok(DeviceSnapshot {
+            endpoint: self.endpoint.to_string(),
+            lights,
+            settings,
+        })
+    }
+
+    /// Bring the light back to a captured state, and the settings if they were captured.
+    /// With `fade`, lights that are on now and in the snapshot fade there first.
+    pub async fn restore(&self, snapshot: &DeviceSnapshot, fade: Option<Duration>) -> Result<()> {
+        let current = self.ecc.lights_get(self.endpoint).await?;
+        if current.lights.len() != snapshot.lights.lights.len() {
+            return Err(Error::Validation(format!(
+                "The device has {} light(s), the snapshot has {}",
+                current.lights.len(),
+                snapshot.lights.lights.len()
+            )));
+        }
+
+        if let Some(fade) = fade {
+            let capabilities = self.capabilities().await?;
+            let light = self.with_capabilities(capabilities);
+            let cancel = CancellationToken::new();
+            let transitions = current
+                .lights
+                .iter()
+                .zip(&snapshot.lights.lights)
+                .enumerate()
+                .filter(|(i, (now, then))| self.index.is_none_or(|x| x == *i) && now.on && then.on)
+                .map(|(i, (_, then))| {
+                    let mut transition = Transition::new(fade).brightness(then.brightness);
+                    if let Some(value) = then.temperature {
+                        transition = transition.temperature(value.kelvin());
+                    }
+                    let cancel = &cancel;
+                    async move { light.channel(i).transition(&transition, cancel).await }
+                });
+            futures::future::try_join_all(transitions).await?;
+        }
+
+        self.put_lights(&snapshot.lights, |_, x| LightPut {
+            on: Some(x.on),
+            brightness: Some(x.brightness),
+            temperature: x.temperature,
+            hue: x.hue,
+            saturation: x.saturation,
+            ..Default::default()
+        })
+        .await?;
+        if let Some(settings) = &snapshot.settings {
+            self.settings_set(&settings.clone().into()).await?;
+        }
+        Ok(())
+    }
+
     // Private
 
+    async fn require(&self, feature: Feature) -> Result<()> {
+        self.features().await?.require(feature)
+    }
+
+    /// Capabilities given with `with_capabilities`, or else those of the accessory info alone,
+    /// which is all that is needed to check for a feature
+    async fn features(&self) -> Result<Capabilities> {
+        match self.capabilities {
+            Some(capabilities) => Ok(capabilities),
+            None => {
+                let accessory_info = self.accessory_info_get().await?;
+                Ok(Capabilities::from_accessory_info(&accessory_info))
+            }
+        }
+    }
+
+    async fn poll(&self) -> Result<Poll> {
+        let (lights, settings, accessory_info) = futures::try_join!(
+            self.ecc.lights_get(self.endpoint),
+            self.ecc.lights_settings_get(self.endpoint),
+            self.accessory_info_get(),
+        )?;
+        let count = lights.lights.len();
+        if self.index.is_some_and(|x| x >= count) {
+            return Err(self.missing(count));
+        }
+        Ok(Poll {
+            lights: lights.lights,
+            settings,
+            display_name: accessory_info.display_name,
+        })
+    }
+
     async fn field_get<F, T>(&self, f: F) -> Result<T>
     where
         F: FnOnce(LightGet) -> T,
     {
         let lights = self.ecc.lights_get(self.endpoint).await?;
-        let value = lights
+        let count = lights.lights.len();
+        let index = self.index.unwrap_or_default();
+        let light = lights
             .lights
             .into_iter()
-            .next()
-            .map(f)
-            .ok_or(anyhow!("No lights found"))?;
-        Ok(value)
+            .nth(index)
+            .ok_or_else(|| self.missing(count))?;
+        Ok(f(light))
     }
 
     async fn set_light<F>(&self, f: F) -> Result<LightsGet>
@@ -112,9 +648,290 @@ impl<'a> Light<'a> {
         F: Fn(LightGet) -> LightPut,
     {
         let lights = self.ecc.lights_get(self.endpoint).await?;
-        let lights_put = lights.lights.into_iter().map(&f).collect::<Vec<_>>();
+        self.put_lights(&lights, |_, x| f(x)).await
+    }
+
+    /// Put the result of `f` for each targeted light, given its index and current state
+    async fn put_lights<F>(&self, lights: &LightsGet, f: F) -> Result<LightsGet>
+    where
+        F: Fn(usize, LightGet) -> LightPut,
+    {
+        let count = lights.lights.len();
+        if self.index.is_some_and(|x| x >= count) || count == 0 {
+            return Err(self.missing(count));
+        }
+        let lights_put = lights
+            .lights
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(i, x)| match self.index {
+                Some(index) if index != i => LightPut::default(),
+                _ => f(i, x),
+            })
+            .collect::<Vec<_>>();
         self.ecc
             .lights_put(self.endpoint, &LightsPut { lights: lights_put })
             .await
     }
+
+    fn missing(&self, count: usize) -> Error {
+        match self.index {
+            Some(index) if count > 0 => Error::NoLightAtIndex { index, count },
+            _ => Error::NoLights,
+        }
+    }
+}
+
+/// Lights controlled together, running each operation on all members concurrently
+#[derive(Clone)]
+pub struct LightGroup<'a> {
+    lights: Vec<Light<'a>>,
+    concurrency: usize,
+}
+
+/// Outcome of an operation on one member of a group
+pub struct MemberResult<'a, T> {
+    pub light: Light<'a>,
+    pub result: Result<T>,
+}
+
+/// Outcome of an operation on every member of a group, in member order
+pub struct GroupReport<'a, T> {
+    pub results: Vec<MemberResult<'a, T>>,
+}
+
+impl<'a, T> GroupReport<'a, T> {
+    /// Whether the operation succeeded on every member
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(|x| x.result.is_ok())
+    }
+
+    pub fn succeeded(&self) -> impl Iterator<Item = &MemberResult<'a, T>> {
+        self.results.iter().filter(|x| x.result.is_ok())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &MemberResult<'a, T>> {
+        self.results.iter().filter(|x| x.result.is_err())
+    }
+}
+
+impl<'a> LightGroup<'a> {
+    // Constructors
+
+    pub fn new(lights: impl IntoIterator<Item = Light<'a>>) -> Self {
+        Self {
+            lights: lights.into_iter().collect(),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Limit how many members are talked to at once, at least 1
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.concurrency = limit.max(1);
+        self
+    }
+
+    // Public
+
+    pub fn lights(&self) -> &[Light<'a>] {
+        &self.lights
+    }
+
+    /// Run an operation on every member concurrently
+    pub async fn run<F, Fut, T>(&self, f: F) -> GroupReport<'a, T>
+    where
+        F: Fn(Light<'a>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let f = &f;
+        let results = stream::iter(self.lights.iter().copied())
+            .map(|light| async move {
+                MemberResult {
+                    light,
+                    result: f(light).await,
+                }
+            })
+            .buffered(self.concurrency)
+            .collect()
+            .await;
+        GroupReport { results }
+    }
+
+    /// Get the capabilities of every device
+    pub async fn capabilities(&self) -> GroupReport<'a, Capabilities> {
+        self.run(|light| async move { light.capabilities().await })
+            .await
+    }
+
+    /// Read the capabilities of each device once and give them to its members,
+    /// so that operations checking for a feature don't read them again.
+    /// Members of devices that couldn't be read are left out, and reported once per device.
+    pub async fn load_capabilities(&self) -> (Self, GroupReport<'a, ()>) {
+        let loaded = self.devices(|_| true).capabilities().await;
+
+        let mut found = Vec::new();
+        let mut failed = Vec::new();
+        for member in loaded.results {
+            match member.result {
+                Ok(capabilities) => found.push((member.light.endpoint, capabilities)),
+                Err(e) => failed.push(MemberResult {
+                    light: member.light,
+                    result: Err(e),
+                }),
+            }
+        }
+        let lights = self
+            .lights
+            .iter()
+            .filter_map(|light| {
+                found
+                    .iter()
+                    .find(|(endpoint, _)| *endpoint == light.endpoint)
+                    .map(|(_, capabilities)| light.with_capabilities(*capabilities))
+            })
+            .collect();
+        let group = Self {
+            lights,
+            concurrency: self.concurrency,
+        };
+        (group, GroupReport { results: failed })
+    }
+
+    /// Check once per device that it is the one its members expect, before changing anything.
+    /// Members of devices that answer as another device, or not at all, are left out
+    /// and reported once per device. Members without an identity are kept unchecked.
+    pub async fn verify_identities(&self) -> (Self, GroupReport<'a, ()>) {
+        let checked = self
+            .devices(|light| light.identity.is_some())
+            .run(|light| async move { light.accessory_info_get().await.map(|_| ()) })
+            .await;
+        let failed = checked
+            .results
+            .into_iter()
+            .filter(|x| x.result.is_err())
+            .collect::<Vec<_>>();
+        let lights = self
+            .lights
+            .iter()
+            .filter(|light| !failed.iter().any(|x| x.light.endpoint == light.endpoint))
+            .copied()
+            .collect();
+        let group = Self {
+            lights,
+            concurrency: self.concurrency,
+        };
+        (group, GroupReport { results: failed })
+    }
+
+    /// Turn on every light
+    pub async fn on(&self) -> GroupReport<'a, ()> {
+        self.run(|light| async move { light.on().await }).await
+    }
+
+    /// Turn off every light
+    pub async fn off(&self) -> GroupReport<'a, ()> {
+        self.run(|light| async move { light.off().await }).await
+    }
+
+    /// Toggle the state of every light
+    pub async fn toggle(&self) -> GroupReport<'a, ()> {
+        self.run(|light| async move { light.toggle().await }).await
+    }
+
+    /// Set temperature from `Kelvin` or `Mired` on every light
+    pub async fn temperature_set(&self, value: impl Into<Kelvin>) -> GroupReport<'a, LightsGet> {
+        let value = value.into();
+        self.run(|light| async move { light.temperature_set(value).await })
+            .await
+    }
+
+    /// Set brightness on every light
+    pub async fn brightness_set(&self, value: Brightness) -> GroupReport<'a, LightsGet> {
+        self.run(|light| async move { light.brightness_set(value).await })
+            .await
+    }
+
+    /// Set temperature resolved against the current one on every light
+    pub async fn temperature_adjust(&self, value: TemperatureValue) -> GroupReport<'a, LightsGet> {
+        self.run(|light| async move { light.temperature_adjust(value).await })
+            .await
+    }
+
+    /// Set brightness resolved against the current one on every light
+    pub async fn brightness_adjust(&self, value: BrightnessValue) -> GroupReport<'a, LightsGet> {
+        self.run(|light| async move { light.brightness_adjust(value).await })
+            .await
+    }
+
+    /// Set colour on every light
+    pub async fn color_set(&self, color: Hsv) -> GroupReport<'a, LightsGet> {
+        self.run(|light| async move { light.color_set(color).await })
+            .await
+    }
+
+    /// Upload and start a scene on every light
+    pub async fn scene_set(&self, scene: &Scene) -> GroupReport<'a, LightsGet> {
+        self.run(|light| async move { light.scene_set(scene).await })
+            .await
+    }
+
+    /// Get the settings of every device
+    pub async fn settings_get(&self) -> GroupReport<'a, LightsSettingsGet> {
+        self.run(|light| async move { light.settings_get().await })
+            .await
+    }
+
+    /// Update the settings of every device
+    pub async fn settings_set(&self, settings: &LightsSettingsPut) -> GroupReport<'a, ()> {
+        self.run(|light| async move { light.settings_set(settings).await })
+            .await
+    }
+
+    /// Fade every light together, stopping early when `cancel` is triggered
+    pub async fn transition(
+        &self,
+        transition: &Transition,
+        cancel: &CancellationToken,
+    ) -> GroupReport<'a, TransitionOutcome> {
+        self.run(|light| async move { light.transition(transition, cancel).await })
+            .await
+    }
+
+    // Private
+
+    /// The first member `include`d of each device, to talk to every device once
+    fn devices(&self, include: impl Fn(&Light<'a>) -> bool) -> Self {
+        let mut devices = Vec::<Light<'a>>::new();
+        for light in self.lights.iter().filter(|x| include(x)) {
+            if !devices.iter().any(|x| x.endpoint == light.endpoint) {
+                devices.push(*light);
+            }
+        }
+        Self {
+            lights: devices,
+            concurrency: self.concurrency,
+        }
+    }
+}
+
+/// Report a missing battery endpoint as unsupported rather than as a status error
+fn battery<T>(result: Result<T>) -> Result<T> {
+    match result {
+        Err(Error::Status { status, .. }) if status == reqwest::StatusCode::NOT_FOUND => {
+            Err(Error::Unsupported("battery"))
+        }
+        x => x,
+    }
+}
+
+/// Duration in milliseconds as stored in the settings
+fn duration_ms(value: Duration) -> Result<u16> {
+    u16::try_from(value.as_millis()).map_err(|_| {
+        Error::Validation(format!(
+            "Duration of {}ms is above the maximum of {}ms",
+            value.as_millis(),
+            u16::MAX
+        ))
+    })
 }
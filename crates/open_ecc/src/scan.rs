@@ -0,0 +1,146 @@
+use crate::{
+    catalogue::Product,
+    contracts::AccessoryInfoGet,
+    ecc::{DEFAULT_PORT, Ecc},
+    error::{Error, Result},
+};
+use futures::{Stream, StreamExt, future::ready, stream};
+use ipnet::IpNet;
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+/// Addresses probed at the same time by default
+pub const DEFAULT_CONCURRENCY: usize = 64;
+/// Time each address has to answer by default
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+/// Networks with a shorter prefix than this, e.g. a /8, are refused
+pub const MAX_HOST_BITS: u8 = 16;
+
+#[derive(Debug, Clone)]
+pub struct ScannedLight {
+    pub address: IpAddr,
+    pub port: u16,
+    pub accessory_info: AccessoryInfoGet,
+}
+
+impl ScannedLight {
+    /// Endpoint usable with `Ecc` and `Light`
+    pub fn endpoint(&self) -> String {
+        SocketAddr::new(self.address, self.port).to_string()
+    }
+}
+
+/// Finds lights by probing every address of a network, for when mDNS is filtered
+pub struct Scanner<'a> {
+    ecc: &'a Ecc,
+    port: u16,
+    concurrency: usize,
+    timeout: Duration,
+}
+
+impl<'a> Scanner<'a> {
+    // Constructors
+
+    pub fn new(ecc: &'a Ecc) -> Self {
+        Self {
+            ecc,
+            port: DEFAULT_PORT,
+            concurrency: DEFAULT_CONCURRENCY,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Addresses probed at the same time, at least one
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Time each address has to answer
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // Public
+
+    /// Probe every host address of the network, yielding lights as they answer
+    pub fn sweep(&self, network: IpNet) -> Result<impl Stream<Item = ScannedLight> + '_> {
+        let host_bits = network.max_prefix_len() - network.prefix_len();
+        if host_bits > MAX_HOST_BITS {
+            return Err(Error::Validation(format!(
+                "Network {} is too large to scan, use a /{} or smaller",
+                network,
+                network.max_prefix_len() - MAX_HOST_BITS
+            )));
+        }
+        let stream = stream::iter(network.hosts())
+            .map(|address| self.probe(address))
+            .buffer_unordered(self.concurrency)
+            .filter_map(ready);
+        Ok(stream)
+    }
+
+    /// Probe every host address of the network, lights ordered by address
+    pub async fn scan(&self, network: IpNet) -> Result<Vec<ScannedLight>> {
+        let mut lights = self.sweep(network)?.collect::<Vec<_>>().await;
+        lights.sort_by_key(|x| x.address);
+        Ok(lights)
+    }
+
+    /// The light at an address, if an Elgato device answers in time
+    pub async fn probe(&self, address: IpAddr) -> Option<ScannedLight> {
+        let endpoint = SocketAddr::new(address, self.port).to_string();
+        let accessory_info =
+            tokio::time::timeout(self.timeout, self.ecc.accessory_info_get(&endpoint))
+                .await
+                .ok()?
+                .ok()?;
+        is_elgato(&accessory_info).then_some(ScannedLight {
+            address,
+            port: self.port,
+            accessory_info,
+        })
+    }
+}
+
+/// Whether the accessory info is that of an Elgato light, rather than another device
+/// that happens to answer with the same fields
+fn is_elgato(info: &AccessoryInfoGet) -> bool {
+    !info.serial_number.is_empty()
+        && (Product::from_accessory_info(info).is_some() || info.product_name.starts_with("Elgato"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_elgato() {
+        let info = |product_name: &str, serial_number: &str| AccessoryInfoGet {
+            product_name: product_name.to_string(),
+            serial_number: serial_number.to_string(),
+            ..Default::default()
+        };
+        assert!(is_elgato(&info("Elgato Key Light", "BW001")));
+        assert!(is_elgato(&info("Elgato Key Light Neo", "BW001")));
+        assert!(!is_elgato(&info("Elgato Key Light", "")));
+        assert!(!is_elgato(&info("Printer", "X1")));
+    }
+
+    #[test]
+    fn test_network_too_large() {
+        let ecc = Ecc::default();
+        let scanner = Scanner::new(&ecc);
+        assert!(scanner.sweep("10.0.0.0/8".parse().unwrap()).is_err());
+        assert!(scanner.sweep("fe80::/64".parse().unwrap()).is_err());
+        assert!(scanner.sweep("10.0.4.0/24".parse().unwrap()).is_ok());
+    }
+}
@@ -1,5 +1,7 @@
-use crate::contracts::JsonErrors;
-use anyhow::{Result, bail};
+use crate::{
+    contracts::JsonErrors,
+    error::{Error, Result},
+};
 use reqwest::Response;
 use serde::de::DeserializeOwned;
 
@@ -7,14 +9,32 @@ pub(crate) async fn deser_response<T>(response: Response) -> Result<T>
 where
     T: DeserializeOwned,
 {
-    if response.status().is_success() {
-        Ok(response.json::<T>().await?)
+    let status = response.status();
+    let bytes = response.bytes().await?;
+    if status.is_success() {
+        Ok(serde_json::from_slice::<T>(&bytes)?)
     } else {
-        let errors = response.json::<JsonErrors>().await?;
-        bail!("{:#?}", errors)
+        Err(status_error(status, &bytes))
     }
 }
 
+pub(crate) async fn check_response(response: Response) -> Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        let bytes = response.bytes().await?;
+        Err(status_error(status, &bytes))
+    }
+}
+
+fn status_error(status: reqwest::StatusCode, bytes: &[u8]) -> Error {
+    let errors = serde_json::from_slice::<JsonErrors>(bytes)
+        .map(|x| x.errors)
+        .unwrap_or_default();
+    Error::Status { status, errors }
+}
+
 pub(crate) mod u8_bool_handler {
     use serde::{Deserialize, Deserializer, Serializer};
 
@@ -55,43 +75,29 @@ pub(crate) mod u8_bool_option_handler {
     }
 }
 
-pub(crate) mod temperature_handler {
-    use crate::helpers::{api_to_kelvin, kelvin_to_api};
-    use serde::{self, Deserialize, Deserializer, Serializer};
-
-    pub fn serialize<S>(value: &u16, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        serializer.serialize_u16(kelvin_to_api(*value))
-    }
-
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<u16, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        Ok(api_to_kelvin(u16::deserialize(deserializer)?))
-    }
-}
-
-pub(crate) mod temperature_option_handler {
-    use crate::helpers::{api_to_kelvin, kelvin_to_api};
-    use serde::{Deserialize, Deserializer, Serializer};
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
 
-    pub fn serialize<S>(value: &Option<u16>, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        match value {
-            Some(x) => serializer.serialize_some(&kelvin_to_api(*x)),
-            None => serializer.serialize_none(),
-        }
+    #[test]
+    fn test_status_error_codes() {
+        let body = br#"{"errors":[{"message":"Invalid value","code":2}]}"#;
+        let error = status_error(StatusCode::BAD_REQUEST, body);
+        assert!(matches!(
+            error,
+            Error::Status {
+                status: StatusCode::BAD_REQUEST,
+                ..
+            }
+        ));
+        assert_eq!(error.codes(), vec![2]);
+        assert!(!error.is_offline());
     }
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<u16>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        Ok(Option::<u16>::deserialize(deserializer)?.map(api_to_kelvin))
+    #[test]
+    fn test_status_error_without_body() {
+        let error = status_error(StatusCode::NOT_FOUND, b"Not Found");
+        assert!(error.codes().is_empty());
     }
 }
@@ -0,0 +1,77 @@
+use crate::{
+    contracts::{LightsGet, LightsSettingsGet},
+    error::Error,
+    light::{GroupReport, LightGroup, MemberResult},
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Captured state of a set of devices, to recall later
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub devices: Vec<DeviceSnapshot>,
+}
+
+/// Captured state of one device
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceSnapshot {
+    pub endpoint: String,
+    pub lights: LightsGet,
+    /// Only captured when asked for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<LightsSettingsGet>,
+}
+
+impl Snapshot {
+    // Constructors
+
+    /// Capture every device of the group once, leaving out those that couldn't be read.
+    /// The report has the outcome for each member.
+    pub async fn capture<'a>(
+        group: &LightGroup<'a>,
+        settings: bool,
+    ) -> (Self, GroupReport<'a, ()>) {
+        let report = group
+            .run(|light| async move { light.capture(settings).await })
+            .await;
+        let mut devices = Vec::<DeviceSnapshot>::new();
+        let mut results = Vec::new();
+        for member in report.results {
+            let result = member.result.map(|device| {
+                if !devices.iter().any(|x| x.endpoint == device.endpoint) {
+                    devices.push(device);
+                }
+            });
+            results.push(MemberResult {
+                light: member.light,
+                result,
+            });
+        }
+        (Self { devices }, GroupReport { results })
+    }
+
+    // Public
+
+    /// State captured for an endpoint, if any
+    pub fn device(&self, endpoint: &str) -> Option<&DeviceSnapshot> {
+        self.devices.iter().find(|x| x.endpoint == endpoint)
+    }
+
+    /// Restore every light of the group that was captured, fading over `fade` if given
+    pub async fn apply<'a>(
+        &self,
+        group: &LightGroup<'a>,
+        fade: Option<Duration>,
+    ) -> GroupReport<'a, ()> {
+        group
+            .run(|light| async move {
+                let device = self.device(light.endpoint()).ok_or_else(|| {
+                    Error::Validation(format!("No state captured for {}", light.endpoint()))
+                })?;
+                light.restore(device, fade).await
+            })
+            .await
+    }
+}
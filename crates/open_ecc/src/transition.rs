@@ -0,0 +1,232 @@
+use crate::{
+    contracts::LightGet,
+    error::{Error, Result},
+    units::{Brightness, Kelvin},
+};
+use std::time::Duration;
+
+/// Time between updates sent to the light, unless configured otherwise
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Slow start
+    EaseIn,
+    /// Slow finish
+    EaseOut,
+    /// Slow start and finish
+    EaseInOut,
+    /// Even steps as perceived by the eye, through CIE lightness for brightness and mired for temperature
+    Perceptual,
+}
+
+impl Easing {
+    /// Map progress [0..=1] in time to progress [0..=1] towards the target
+    pub fn curve(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear | Self::Perceptual => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+        }
+    }
+
+    /// Brightness [0..=100] at progress `t` between `from` and `to`
+    pub fn brightness(&self, from: u8, to: u8, t: f64) -> u8 {
+        let t = self.curve(t);
+        let value = match self {
+            Self::Perceptual => lightness_to_brightness(lerp(
+                brightness_to_lightness(from as f64),
+                brightness_to_lightness(to as f64),
+                t,
+            )),
+            _ => lerp(from as f64, to as f64, t),
+        };
+        value.round().clamp(0.0, 100.0) as u8
+    }
+
+    /// Temperature in Kelvin at progress `t` between `from` and `to`
+    pub fn temperature(&self, from: u16, to: u16, t: f64) -> u16 {
+        let t = self.curve(t);
+        let value = match self {
+            Self::Perceptual => 1e6 / lerp(1e6 / from as f64, 1e6 / to as f64, t),
+            _ => lerp(from as f64, to as f64, t),
+        };
+        value.round() as u16
+    }
+}
+
+/// A fade of brightness and/or temperature, driven from the client
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub brightness: Option<Brightness>,
+    pub temperature: Option<Kelvin>,
+    pub duration: Duration,
+    pub easing: Easing,
+    /// Time between updates sent to the light
+    pub interval: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionOutcome {
+    Completed,
+    Cancelled,
+}
+
+impl Transition {
+    // Constructors
+
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            brightness: None,
+            temperature: None,
+            duration,
+            easing: Easing::default(),
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+
+    pub fn brightness(mut self, value: Brightness) -> Self {
+        self.brightness = Some(value);
+        self
+    }
+
+    pub fn temperature(mut self, value: Kelvin) -> Self {
+        self.temperature = Some(value);
+        self
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    // Public
+
+    pub fn validate(&self) -> Result<()> {
+        if self.brightness.is_none() && self.temperature.is_none() {
+            return Err(Error::Validation(
+                "Transition needs a target brightness or temperature".to_string(),
+            ));
+        }
+        if self.interval.is_zero() {
+            return Err(Error::Validation(
+                "Transition interval must be above zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Number of updates sent over the duration, at least one
+    pub fn steps(&self) -> u32 {
+        (self.duration.as_millis() / self.interval.as_millis().max(1)).max(1) as u32
+    }
+
+    /// Values for a light at step `step` of `steps()`, starting from `from`
+    pub fn at(&self, from: &LightGet, step: u32) -> (Option<Brightness>, Option<Kelvin>) {
+        let t = step as f64 / self.steps() as f64;
+        let brightness = self.brightness.map(|to| {
+            let value = self.easing.brightness(from.brightness.get(), to.get(), t);
+            Brightness::clamped(value as i32)
+        });
+        let temperature = self.temperature.map(|to| match from.temperature {
+            Some(from) => {
+                let value = self.easing.temperature(from.kelvin().get(), to.get(), t);
+                Kelvin::clamped(value as i32)
+            }
+            None => to,
+        });
+        (brightness, temperature)
+    }
+}
+
+fn lerp(from: f64, to: f64, t: f64) -> f64 {
+    from + (to - from) * t
+}
+
+/// CIE 1976 lightness L* of a brightness treated as relative luminance
+fn brightness_to_lightness(brightness: f64) -> f64 {
+    let y = brightness / 100.0;
+    if y > 216.0 / 24389.0 {
+        116.0 * y.cbrt() - 16.0
+    } else {
+        y * 24389.0 / 27.0
+    }
+}
+
+fn lightness_to_brightness(lightness: f64) -> f64 {
+    let y = if lightness > 8.0 {
+        ((lightness + 16.0) / 116.0).powi(3)
+    } else {
+        lightness * 27.0 / 24389.0
+    };
+    y * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curve_endpoints() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::Perceptual,
+        ] {
+            assert_eq!(easing.brightness(10, 90, 0.0), 10);
+            assert_eq!(easing.brightness(10, 90, 1.0), 90);
+            assert_eq!(easing.temperature(2900, 7000, 0.0), 2900);
+            assert_eq!(easing.temperature(2900, 7000, 1.0), 7000);
+        }
+    }
+
+    #[test]
+    fn test_curve_midpoints() {
+        assert_eq!(Easing::Linear.brightness(0, 100, 0.5), 50);
+        assert_eq!(Easing::EaseIn.brightness(0, 100, 0.5), 25);
+        assert_eq!(Easing::EaseOut.brightness(0, 100, 0.5), 75);
+        assert_eq!(Easing::EaseInOut.brightness(0, 100, 0.5), 50);
+        // Half of the perceived lightness is far below half of the luminance
+        assert_eq!(Easing::Perceptual.brightness(0, 100, 0.5), 18);
+        // Halfway in mired is closer to the warm end
+        assert_eq!(Easing::Perceptual.temperature(2900, 7000, 0.5), 4101);
+    }
+
+    #[test]
+    fn test_validate() {
+        let transition = Transition::new(Duration::from_secs(1));
+        assert!(transition.validate().is_err());
+        assert!(
+            transition
+                .interval(Duration::ZERO)
+                .brightness(Brightness::new(30).unwrap())
+                .validate()
+                .is_err()
+        );
+        assert!(
+            transition
+                .brightness(Brightness::new(30).unwrap())
+                .temperature(Kelvin::new(3200).unwrap())
+                .validate()
+                .is_ok()
+        );
+        assert_eq!(transition.steps(), 10);
+    }
+}
@@ -0,0 +1,376 @@
+use crate::{
+    error::{Error, Result},
+    helpers::{api_to_kelvin, kelvin_to_api},
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Brightness in percent.
+/// Values above 100 read from a device are clamped rather than failing the whole read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(into = "u8")]
+pub struct Brightness(u8);
+
+/// Colour temperature in Kelvin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u16", into = "u16")]
+pub struct Kelvin(u16);
+
+/// Colour temperature in mired, one million divided by Kelvin.
+/// Not the scale used by the device, see `ApiTemperature`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u16", into = "u16")]
+pub struct Mired(u16);
+
+/// Colour temperature as sent to and reported by the device, a linear scale from
+/// 143 at 2900K to 344 at 7000K.
+/// Values read from a device are kept as they are, even outside of the range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ApiTemperature(u16);
+
+impl Brightness {
+    pub const MIN: u8 = 0;
+    pub const MAX: u8 = 100;
+
+    // Constructors
+
+    /// Range: 0 - 100
+    pub fn new(value: u8) -> Result<Self> {
+        match value {
+            Self::MIN..=Self::MAX => Ok(Self(value)),
+            _ => Err(Error::Validation(format!(
+                "Brightness {} is outside of {} - {}",
+                value,
+                Self::MIN,
+                Self::MAX
+            ))),
+        }
+    }
+
+    /// Nearest brightness in range
+    pub fn clamped(value: i32) -> Self {
+        Self(value.clamp(Self::MIN as i32, Self::MAX as i32) as u8)
+    }
+
+    // Public
+
+    pub fn get(&self) -> u8 {
+        self.0
+    }
+}
+
+impl Kelvin {
+    pub const MIN: u16 = 2900;
+    pub const MAX: u16 = 7000;
+
+    // Constructors
+
+    /// Range: 2900 - 7000
+    pub fn new(value: u16) -> Result<Self> {
+        match value {
+            Self::MIN..=Self::MAX => Ok(Self(value)),
+            _ => Err(Error::Validation(format!(
+                "Temperature {}K is outside of {} - {}",
+                value,
+                Self::MIN,
+                Self::MAX
+            ))),
+        }
+    }
+
+    /// Nearest temperature in range
+    pub fn clamped(value: i32) -> Self {
+        Self(value.clamp(Self::MIN as i32, Self::MAX as i32) as u16)
+    }
+
+    // Public
+
+    pub fn get(&self) -> u16 {
+        self.0
+    }
+}
+
+impl Mired {
+    /// `Kelvin::MAX` in mired
+    pub const MIN: u16 = ((1_000_000 + Kelvin::MAX as u32 / 2) / Kelvin::MAX as u32) as u16;
+    /// `Kelvin::MIN` in mired
+    pub const MAX: u16 = ((1_000_000 + Kelvin::MIN as u32 / 2) / Kelvin::MIN as u32) as u16;
+
+    // Constructors
+
+    /// Range: 143 - 345, the Kelvin range in mired
+    pub fn new(value: u16) -> Result<Self> {
+        match value {
+            Self::MIN..=Self::MAX => Ok(Self(value)),
+            _ => Err(Error::Validation(format!(
+                "Temperature {} mired is outside of {} - {}",
+                value,
+                Self::MIN,
+                Self::MAX
+            ))),
+        }
+    }
+
+    // Public
+
+    pub fn get(&self) -> u16 {
+        self.0
+    }
+}
+
+impl ApiTemperature {
+    pub const MIN: u16 = 143;
+    pub const MAX: u16 = 344;
+
+    // Constructors
+
+    /// Range: 143 - 344, sent to the device without any conversion
+    pub fn new(value: u16) -> Result<Self> {
+        match value {
+            Self::MIN..=Self::MAX => Ok(Self(value)),
+            _ => Err(Error::Validation(format!(
+                "API temperature {} is outside of {} - {}",
+                value,
+                Self::MIN,
+                Self::MAX
+            ))),
+        }
+    }
+
+    // Public
+
+    pub fn get(&self) -> u16 {
+        self.0
+    }
+
+    /// Whether the value is one the device accepts
+    pub fn is_valid(&self) -> bool {
+        (Self::MIN..=Self::MAX).contains(&self.0)
+    }
+
+    /// Temperature in Kelvin, rounded to steps of 50K
+    pub fn kelvin(&self) -> Kelvin {
+        Kelvin(api_to_kelvin(self.0))
+    }
+}
+
+impl Default for ApiTemperature {
+    fn default() -> Self {
+        Self(Self::MIN)
+    }
+}
+
+impl From<Brightness> for u8 {
+    fn from(value: Brightness) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<u8> for Brightness {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        Self::new(value)
+    }
+}
+
+impl<'de> Deserialize<'de> for Brightness {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        u8::deserialize(deserializer).map(|x| Self::clamped(x as i32))
+    }
+}
+
+impl From<Kelvin> for u16 {
+    fn from(value: Kelvin) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<u16> for Kelvin {
+    type Error = Error;
+
+    fn try_from(value: u16) -> Result<Self> {
+        Self::new(value)
+    }
+}
+
+impl From<Mired> for u16 {
+    fn from(value: Mired) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<u16> for Mired {
+    type Error = Error;
+
+    fn try_from(value: u16) -> Result<Self> {
+        Self::new(value)
+    }
+}
+
+impl From<Kelvin> for Mired {
+    fn from(value: Kelvin) -> Self {
+        Self((1e6 / value.0 as f64).round() as u16)
+    }
+}
+
+impl From<Mired> for Kelvin {
+    fn from(value: Mired) -> Self {
+        Self::clamped((1e6 / value.0 as f64).round() as i32)
+    }
+}
+
+impl From<Kelvin> for ApiTemperature {
+    fn from(value: Kelvin) -> Self {
+        Self(kelvin_to_api(value.0))
+    }
+}
+
+impl From<Mired> for ApiTemperature {
+    fn from(value: Mired) -> Self {
+        Kelvin::from(value).into()
+    }
+}
+
+impl From<ApiTemperature> for Kelvin {
+    fn from(value: ApiTemperature) -> Self {
+        value.kelvin()
+    }
+}
+
+/// Parses `50` or `50%`
+impl FromStr for Brightness {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        s.strip_suffix('%')
+            .unwrap_or(s)
+            .parse::<u8>()
+            .map_err(|_| Error::Validation(format!("Invalid brightness '{}'", s)))
+            .and_then(Self::new)
+    }
+}
+
+/// Parses `3200` or `3200K`
+impl FromStr for Kelvin {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        s.strip_suffix(['k', 'K'])
+            .unwrap_or(s)
+            .parse::<u16>()
+            .map_err(|_| Error::Validation(format!("Invalid temperature '{}'", s)))
+            .and_then(Self::new)
+    }
+}
+
+/// Parses `250` or `250mired`
+impl FromStr for Mired {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        s.strip_suffix("mired")
+            .unwrap_or(s)
+            .trim()
+            .parse::<u16>()
+            .map_err(|_| Error::Validation(format!("Invalid temperature '{}'", s)))
+            .and_then(Self::new)
+    }
+}
+
+impl FromStr for ApiTemperature {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.trim()
+            .parse::<u16>()
+            .map_err(|_| Error::Validation(format!("Invalid API temperature '{}'", s)))
+            .and_then(Self::new)
+    }
+}
+
+impl fmt::Display for Brightness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+impl fmt::Display for Kelvin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}K", self.0)
+    }
+}
+
+impl fmt::Display for Mired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} mired", self.0)
+    }
+}
+
+impl fmt::Display for ApiTemperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_constructors() {
+        assert!(Brightness::new(100).is_ok());
+        assert!(Brightness::new(101).is_err());
+        assert!(Kelvin::new(2900).is_ok());
+        assert!(Kelvin::new(2850).is_err());
+        assert!(Kelvin::new(7050).is_err());
+        assert!(Mired::new(100).is_err());
+        assert_eq!((Mired::MIN, Mired::MAX), (143, 345));
+        assert!(ApiTemperature::new(344).is_ok());
+        assert!(ApiTemperature::new(345).is_err());
+        assert_eq!(Brightness::clamped(-5).get(), 0);
+        assert_eq!(Kelvin::clamped(1900).get(), 2900);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let kelvin = Kelvin::new(4000).unwrap();
+        assert_eq!(Mired::from(kelvin).get(), 250);
+        assert_eq!(Kelvin::from(Mired::new(250).unwrap()), kelvin);
+        assert_eq!(Kelvin::from(Mired::new(Mired::MAX).unwrap()).get(), 2900);
+
+        let api = ApiTemperature::from(Kelvin::new(2900).unwrap());
+        assert_eq!(api.get(), 143);
+        assert_eq!(api.kelvin().get(), 2900);
+        // The exact device value is kept, only the conversion to Kelvin rounds
+        let api = ApiTemperature::new(200).unwrap();
+        assert_eq!(api.get(), 200);
+        assert_eq!(api.kelvin().get(), 4050);
+    }
+
+    #[test]
+    fn test_serde() {
+        assert_eq!(
+            serde_json::to_string(&Brightness::new(42).unwrap()).unwrap(),
+            "42"
+        );
+        assert!(serde_json::from_str::<Kelvin>("2000").is_err());
+        // Whatever the device reports is accepted
+        let brightness = serde_json::from_str::<Brightness>("150").unwrap();
+        assert_eq!(brightness.get(), 100);
+        let api = serde_json::from_str::<ApiTemperature>("400").unwrap();
+        assert!(!api.is_valid());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("50%".parse::<Brightness>().unwrap().get(), 50);
+        assert_eq!("3200K".parse::<Kelvin>().unwrap().get(), 3200);
+        assert_eq!("250mired".parse::<Mired>().unwrap().get(), 250);
+        assert!("8000".parse::<Kelvin>().is_err());
+    }
+}
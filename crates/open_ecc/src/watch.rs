@@ -0,0 +1,180 @@
+use crate::{
+    contracts::{LightGet, LightsSettingsGet},
+    units::{Brightness, Kelvin},
+};
+use std::fmt;
+
+/// A change noticed between two polls of a device
+#[derive(Debug, Clone, PartialEq)]
+pub enum LightEvent {
+    Power {
+        index: usize,
+        on: bool,
+    },
+    Brightness {
+        index: usize,
+        value: Brightness,
+    },
+    Temperature {
+        index: usize,
+        value: Kelvin,
+    },
+    Color {
+        index: usize,
+        /// Range: 0 - 360
+        hue: f32,
+        /// Range: 0 - 100
+        saturation: f32,
+    },
+    Settings(LightsSettingsGet),
+    DisplayName(String),
+}
+
+impl fmt::Display for LightEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Power { index, on } => {
+                write!(f, "light {} {}", index, if *on { "on" } else { "off" })
+            }
+            Self::Brightness { index, value } => write!(f, "light {} brightness {}", index, value),
+            Self::Temperature { index, value } => {
+                write!(f, "light {} temperature {}", index, value)
+            }
+            Self::Color {
+                index,
+                hue,
+                saturation,
+            } => write!(
+                f,
+                "light {} color hue {} saturation {}",
+                index,
+                hue.round(),
+                saturation.round()
+            ),
+            Self::Settings(x) => write!(
+                f,
+                "settings power on behavior {}, brightness {}, temperature {}",
+                x.power_on_behavior,
+                x.power_on_brightness,
+                x.power_on_temperature.kelvin()
+            ),
+            Self::DisplayName(x) => write!(f, "display name '{}'", x),
+        }
+    }
+}
+
+/// Everything a watch compares between polls
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Poll {
+    pub lights: Vec<LightGet>,
+    pub settings: LightsSettingsGet,
+    pub display_name: String,
+}
+
+impl Poll {
+    /// Events that turn `self` into `next`, limited to the light at `index` if given
+    pub fn changes(&self, next: &Poll, index: Option<usize>) -> Vec<LightEvent> {
+        let mut events = Vec::new();
+
+        for (i, (before, after)) in self.lights.iter().zip(&next.lights).enumerate() {
+            if index.is_some_and(|x| x != i) {
+                continue;
+            }
+            if before.on != after.on {
+                events.push(LightEvent::Power {
+                    index: i,
+                    on: after.on,
+                });
+            }
+            if before.brightness != after.brightness {
+                events.push(LightEvent::Brightness {
+                    index: i,
+                    value: after.brightness,
+                });
+            }
+            if let Some(value) = after.temperature
+                && before.temperature != after.temperature
+            {
+                events.push(LightEvent::Temperature {
+                    index: i,
+                    value: value.kelvin(),
+                });
+            }
+            if let (Some(hue), Some(saturation)) = (after.hue, after.saturation)
+                && (before.hue, before.saturation) != (after.hue, after.saturation)
+            {
+                events.push(LightEvent::Color {
+                    index: i,
+                    hue,
+                    saturation,
+                });
+            }
+        }
+
+        if self.settings != next.settings {
+            events.push(LightEvent::Settings(next.settings.clone()));
+        }
+        if self.display_name != next.display_name {
+            events.push(LightEvent::DisplayName(next.display_name.clone()));
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poll(lights: Vec<LightGet>) -> Poll {
+        Poll {
+            lights,
+            settings: LightsSettingsGet::default(),
+            display_name: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_changes() {
+        let light = LightGet {
+            on: false,
+            brightness: Brightness::new(20).unwrap(),
+            temperature: Some(Kelvin::new(4950).unwrap().into()),
+            ..Default::default()
+        };
+        let before = poll(vec![light; 2]);
+        assert!(before.changes(&before, None).is_empty());
+
+        let mut after = before.clone();
+        after.lights[0].on = true;
+        after.lights[1].brightness = Brightness::new(50).unwrap();
+        after.lights[1].temperature = None;
+        after.lights[1].hue = Some(30.0);
+        after.lights[1].saturation = Some(100.0);
+        after.display_name = "Desk".to_string();
+
+        assert_eq!(
+            before.changes(&after, None),
+            vec![
+                LightEvent::Power { index: 0, on: true },
+                LightEvent::Brightness {
+                    index: 1,
+                    value: Brightness::new(50).unwrap()
+                },
+                LightEvent::Color {
+                    index: 1,
+                    hue: 30.0,
+                    saturation: 100.0
+                },
+                LightEvent::DisplayName("Desk".to_string()),
+            ]
+        );
+        assert_eq!(
+            before.changes(&after, Some(0)),
+            vec![
+                LightEvent::Power { index: 0, on: true },
+                LightEvent::DisplayName("Desk".to_string()),
+            ]
+        );
+    }
+}
@@ -1,21 +1,126 @@
 use clap::{Subcommand, ValueEnum};
+use ipnet::IpNet;
+use open_ecc::{
+    adjust::{BrightnessValue, TemperatureValue},
+    color::Hsv,
+    units::{Brightness, Kelvin},
+};
 use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, str::FromStr, time::Duration};
 
 #[derive(clap::Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub(crate) struct Args {
     #[command(subcommand)]
     pub command: Commands,
+    /// Only control the light at this index on devices with multiple lights
+    #[arg(long, short, global = true)]
+    pub index: Option<usize>,
+    #[command(flatten)]
+    pub selection: Selection,
 }
 
-#[derive(Subcommand, Debug)]
+/// Which of the configured lights to control, every light when none are given
+#[derive(clap::Args, Debug, Default)]
+pub(crate) struct Selection {
+    /// Control the light with this name, can be repeated
+    #[arg(long = "light", global = true, value_name = "NAME")]
+    pub lights: Vec<String>,
+    /// Control the lights in this group, can be repeated
+    #[arg(long = "group", global = true, value_name = "NAME")]
+    pub groups: Vec<String>,
+    /// Control the lights with this tag, can be repeated
+    #[arg(long = "tag", global = true)]
+    pub tags: Vec<String>,
+    /// Control every light
+    #[arg(long, global = true, conflicts_with_all = ["lights", "groups", "tags"])]
+    pub all: bool,
+}
+
+impl Selection {
+    /// No light, group or tag was given
+    pub fn is_all(&self) -> bool {
+        self.all || (self.lights.is_empty() && self.groups.is_empty() && self.tags.is_empty())
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
 pub(crate) enum Commands {
-    /// Set the brightness level
+    /// Set the brightness level, e.g. 50, +10, -10 or -25%
     #[command(visible_alias = "b")]
-    Brightness { value: u8 },
-    /// Set the temperature
+    Brightness {
+        #[arg(allow_hyphen_values = true)]
+        value: BrightnessValue,
+    },
+    /// Set the temperature, e.g. 3200, +200, 250mired or a preset like tungsten, daylight or candle
     #[command(visible_alias = "k")]
-    Temperature { value: u16 },
+    Temperature {
+        #[arg(allow_hyphen_values = true)]
+        value: TemperatureValue,
+    },
+    /// Set the colour, e.g. #ff8800, hsv(30,100,100) or orange
+    #[command(visible_alias = "c")]
+    Color { value: Hsv },
+    /// Gradually change the brightness and/or temperature, ctrl-c stops where it is
+    #[command(visible_alias = "f")]
+    Fade {
+        /// Target brightness [range: 0-100]
+        #[arg(long)]
+        to: Option<Brightness>,
+        /// Target temperature [range: 2900-7000]
+        #[arg(long)]
+        kelvin: Option<Kelvin>,
+        /// Duration of the fade, e.g. 4s or 1m30s
+        #[arg(long, default_value = "1s", value_parser = humantime::parse_duration)]
+        over: Duration,
+        /// Curve the fade follows
+        #[arg(long, value_enum, default_value_t = Easing::Linear)]
+        easing: Easing,
+    },
+    /// Save and recall the state of the lights, or start animated Light Strip scenes
+    #[command(visible_alias = "s")]
+    Scene {
+        #[command(subcommand)]
+        command: SceneCommands,
+    },
+    /// Show or change the power-on defaults and fade durations
+    Settings {
+        #[command(subcommand)]
+        command: SettingsCommands,
+    },
+    /// Manage the favourites and auto mode of the remote control
+    Favourites {
+        #[command(subcommand)]
+        command: FavouritesCommands,
+    },
+    /// Show the charge or change the energy saving of lights with a battery
+    Battery {
+        #[command(subcommand)]
+        command: BatteryCommands,
+    },
+    /// Write the identity, settings and state of every light to a JSON or TOML file
+    Backup {
+        /// Path to write, TOML for a .toml extension and JSON otherwise
+        file: PathBuf,
+    },
+    /// Restore lights from a backup, matched by serial number or MAC address
+    Restore {
+        /// Path to a file written by `ecc backup`
+        file: PathBuf,
+        /// Copy the settings and favourites of this serial number or MAC address onto every light instead
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Print the power, brightness and temperature of each light
+    Status {
+        #[arg(long, short, value_enum, default_value_t = Output::Table)]
+        output: Output,
+    },
+    /// Print the product, firmware, identity and network of each device
+    Info {
+        #[arg(long, short, value_enum, default_value_t = Output::Table)]
+        output: Output,
+    },
     /// Toggle the current state of the light
     #[command(visible_alias = "t")]
     Toggle,
@@ -25,12 +130,57 @@ pub(crate) enum Commands {
     /// Turn the light off
     #[command(visible_alias = "0")]
     Off,
+    /// Print changes to the lights as they happen, until stopped
+    Watch {
+        /// Time between polls, e.g. 500ms or 2s
+        #[arg(long, default_value = "1s", value_parser = humantime::parse_duration)]
+        interval: Duration,
+    },
+    /// Name and tag lights
+    #[command(visible_alias = "l")]
+    Lights {
+        #[command(subcommand)]
+        command: LightsCommands,
+    },
+    /// Gather named lights into groups
+    #[command(visible_alias = "g")]
+    Group {
+        #[command(subcommand)]
+        command: GroupCommands,
+    },
     /// Set endpoints by providing space seperated IPs or host names
     #[command(visible_alias = "e")]
     Endpoints {
         /// Endpoints to save in config
         endpoints: Vec<String>,
     },
+    /// Discover lights on the local network
+    #[command(visible_alias = "d")]
+    Discover {
+        /// Seconds to browse for
+        #[arg(long, default_value_t = 3)]
+        timeout: u64,
+        /// Add discovered lights to the endpoints in config
+        #[arg(long)]
+        save: bool,
+    },
+    /// Find lights by probing every address of a network, for when discovery is blocked
+    Scan {
+        /// Network to probe, e.g. 10.0.4.0/24
+        network: IpNet,
+        /// Port the lights listen on
+        #[arg(long, default_value_t = open_ecc::ecc::DEFAULT_PORT)]
+        port: u16,
+        /// Addresses probed at the same time
+        #[arg(long, default_value_t = open_ecc::scan::DEFAULT_CONCURRENCY)]
+        concurrency: usize,
+        /// Time each address has to answer, e.g. 500ms
+        #[arg(long, default_value = "500ms", value_parser = humantime::parse_duration)]
+        timeout: Duration,
+        /// Add the lights found to the endpoints in config
+        #[arg(long)]
+        save: bool,
+    },
     /// Configure WiFi settings
     #[command(visible_alias = "w")]
     Wifi {
@@ -49,6 +199,208 @@ pub(crate) enum Commands {
     },
 }
 
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum LightsCommands {
+    /// Add a light, or replace the one with the same name
+    Add {
+        name: String,
+        /// IP or host name
+        endpoint: String,
+        /// Tags to select the light by
+        tags: Vec<String>,
+    },
+    /// Remove a light, and take it out of its groups
+    #[command(visible_alias = "rm")]
+    Remove { name: String },
+    /// Print the named lights
+    #[command(visible_alias = "ls")]
+    List,
+    /// Look for named lights that no longer answer at their saved address
+    Resolve {
+        /// Names of the lights, all of them when none are given
+        names: Vec<String>,
+        /// Also probe every address of the network each light was last seen on,
+        /// for when mDNS is filtered
+        #[arg(long)]
+        scan: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum GroupCommands {
+    /// Add lights to a group, creating it if needed
+    Add {
+        name: String,
+        /// Names of the lights
+        #[arg(required = true)]
+        lights: Vec<String>,
+    },
+    /// Take lights out of a group, or delete the group when none are given
+    #[command(visible_alias = "rm")]
+    Remove {
+        name: String,
+        /// Names of the lights
+        lights: Vec<String>,
+    },
+    /// Print the groups and their lights
+    #[command(visible_alias = "ls")]
+    List,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum SceneCommands {
+    /// Upload and start a scene from a JSON or TOML file
+    Set {
+        /// Path to the scene file
+        file: PathBuf,
+    },
+    /// Save the current state of the lights under a name
+    Save {
+        name: String,
+        /// Also save the power-on defaults, fade durations and favourites
+        #[arg(long)]
+        settings: bool,
+    },
+    /// Bring the lights back to a saved state
+    Load {
+        name: String,
+        /// Fade brightness and temperature over a duration, e.g. 2s
+        #[arg(long, value_parser = humantime::parse_duration)]
+        fade: Option<Duration>,
+    },
+    /// Print the saved scenes
+    #[command(visible_alias = "ls")]
+    List,
+    /// Delete a saved scene
+    #[command(visible_alias = "rm")]
+    Remove { name: String },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum SettingsCommands {
+    /// Print the settings of each light
+    Get,
+    /// Change the given settings, leaving the rest as they are
+    Set {
+        /// Whether the light turns on when it gets power
+        #[arg(long, value_enum)]
+        power_on: Option<PowerOnBehavior>,
+        /// Brightness after getting power [range: 0-100]
+        #[arg(long)]
+        power_on_brightness: Option<Brightness>,
+        /// Temperature after getting power [range: 2900-7000]
+        #[arg(long)]
+        power_on_kelvin: Option<Kelvin>,
+        /// Fade in duration when turned on, e.g. 100ms
+        #[arg(long, value_parser = humantime::parse_duration)]
+        switch_on: Option<Duration>,
+        /// Fade out duration when turned off, e.g. 300ms
+        #[arg(long, value_parser = humantime::parse_duration)]
+        switch_off: Option<Duration>,
+        /// Duration of brightness, temperature and colour changes, e.g. 100ms
+        #[arg(long, value_parser = humantime::parse_duration)]
+        color_change: Option<Duration>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum FavouritesCommands {
+    /// Print the favourites of each light
+    #[command(visible_alias = "ls")]
+    List,
+    /// Append a favourite
+    Add {
+        /// Brightness [range: 0-100]
+        #[arg(long)]
+        brightness: Brightness,
+        /// Temperature [range: 2900-7000]
+        #[arg(long)]
+        kelvin: Kelvin,
+    },
+    /// Remove the favourite at an index, as shown by list
+    #[command(visible_alias = "rm")]
+    Remove { index: usize },
+    /// Remove every favourite
+    Clear,
+    /// Set the ambient light level in lux that auto mode aims for
+    AutoMode { lux: u16 },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum BatteryCommands {
+    /// Print the charge, power source and energy saving of each light
+    Get,
+    /// Change the given energy saving settings, leaving the rest as they are
+    Set {
+        /// Turn energy saving on or off
+        #[arg(long, value_enum)]
+        energy_saving: Option<Toggle>,
+        /// Battery level in percent below which energy saving starts [range: 0-100]
+        #[arg(long)]
+        min_level: Option<f32>,
+        /// Limit the brightness while saving energy [range: 0-100], or `off`
+        #[arg(long)]
+        brightness_cap: Option<BrightnessCap>,
+        /// Turn WiFi off while saving energy
+        #[arg(long, value_enum)]
+        disable_wifi: Option<Toggle>,
+        /// Power the light directly from the charger, bypassing the battery
+        #[arg(long, value_enum)]
+        bypass: Option<Toggle>,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum Toggle {
+    Off,
+    On,
+}
+
+impl From<Toggle> for bool {
+    fn from(value: Toggle) -> Self {
+        matches!(value, Toggle::On)
+    }
+}
+
+/// A brightness limit, or `off` for none
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BrightnessCap(pub Option<Brightness>);
+
+impl FromStr for BrightnessCap {
+    type Err = open_ecc::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self(None)),
+            _ => s.parse().map(|x| Self(Some(x))),
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Output {
+    /// Aligned columns for reading
+    Table,
+    Json,
+    Yaml,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum PowerOnBehavior {
+    Off,
+    On,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Even steps as perceived by the eye
+    Perceptual,
+}
+
 #[derive(ValueEnum, Debug, Clone, Serialize, Deserialize)]
 pub(crate) enum WifiSecurity {
     None,
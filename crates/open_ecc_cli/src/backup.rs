@@ -0,0 +1,81 @@
+use crate::outcome::Outcome;
+use anyhow::{Context, Result, anyhow};
+use open_ecc::{
+    backup::{Backup, DeviceBackup},
+    light::LightGroup,
+};
+use std::{fs, path::Path};
+
+/// Back up every light, leaving out and reporting those that can't be read
+pub(crate) async fn save(lights: &LightGroup<'_>, path: &Path) -> Result<Outcome> {
+    let report = lights
+        .run(|light| async move { light.backup().await })
+        .await;
+    let outcome = Outcome::from_report(&report);
+    let mut devices = Vec::<DeviceBackup>::new();
+    for member in report.results {
+        if let Ok(device) = member.result
+            && !devices
+                .iter()
+                .any(|x| x.state.endpoint == device.state.endpoint)
+        {
+            devices.push(device);
+        }
+    }
+    if devices.is_empty() {
+        // Leave any previous backup as it is
+        return Ok(outcome);
+    }
+
+    let backup = Backup::new(devices);
+    let contents = match is_toml(path) {
+        true => toml::to_string(&backup).context("Failed to serialize the backup")?,
+        false => serde_json::to_string_pretty(&backup).context("Failed to serialize the backup")?,
+    };
+    fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!(
+        "Backed up {} light(s) to {}",
+        backup.devices.len(),
+        path.display()
+    );
+    Ok(outcome)
+}
+
+/// Restore every light from the device it was backed up as, or from `from` only
+pub(crate) async fn restore(
+    lights: &LightGroup<'_>,
+    path: &Path,
+    from: Option<&str>,
+) -> Result<Outcome> {
+    let backup = load(path)?;
+    let report = match from {
+        Some(id) => {
+            let device = backup.find_by_id(id).ok_or_else(|| {
+                anyhow!(
+                    "No light with serial number or MAC address '{}' in the backup",
+                    id
+                )
+            })?;
+            device.clone_settings(lights).await
+        }
+        None => backup.restore(lights).await,
+    };
+    Ok(Outcome::from_report(&report))
+}
+
+fn load(path: &Path) -> Result<Backup> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let backup: Backup = match is_toml(path) {
+        true => toml::from_str(&contents)
+            .with_context(|| format!("Backup file {} is malformed", path.display()))?,
+        false => serde_json::from_str(&contents)
+            .with_context(|| format!("Backup file {} is malformed", path.display()))?,
+    };
+    backup.validate()?;
+    Ok(backup)
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().and_then(|x| x.to_str()) == Some("toml")
+}
@@ -0,0 +1,96 @@
+use crate::{args::BatteryCommands, outcome::Outcome};
+use anyhow::{Result, bail};
+use open_ecc::{
+    contracts::{
+        AdjustBrightnessPut, BatteryInfo, BatterySettingsGet, BatterySettingsPut, EnergySavingPut,
+    },
+    light::LightGroup,
+};
+
+pub(crate) async fn run(lights: &LightGroup<'_>, command: BatteryCommands) -> Result<Outcome> {
+    let outcome = match command {
+        BatteryCommands::Get => {
+            let report = lights
+                .run(|light| async move {
+                    let info = light.battery_info_get().await?;
+                    let settings = light.battery_settings_get().await?;
+                    Ok((info, settings))
+                })
+                .await;
+            for member in &report.results {
+                if let Ok((info, settings)) = &member.result {
+                    print(member.light.endpoint(), info, settings);
+                }
+            }
+            Outcome::from_report(&report)
+        }
+        BatteryCommands::Set {
+            energy_saving,
+            min_level,
+            brightness_cap,
+            disable_wifi,
+            bypass,
+        } => {
+            let energy_saving_given = energy_saving.is_some()
+                || min_level.is_some()
+                || brightness_cap.is_some()
+                || disable_wifi.is_some();
+            if !energy_saving_given && bypass.is_none() {
+                bail!("No settings given, see `ecc battery set --help`");
+            }
+            let payload = BatterySettingsPut {
+                energy_saving: energy_saving_given.then(|| EnergySavingPut {
+                    enable: energy_saving.map(Into::into),
+                    minimum_battery_level: min_level,
+                    disable_wifi: disable_wifi.map(Into::into),
+                    adjust_brightness: brightness_cap.map(|x| AdjustBrightnessPut {
+                        enable: Some(x.0.is_some()),
+                        brightness: x.0.map(|x| x.get() as f32),
+                    }),
+                }),
+                bypass: bypass.map(Into::into),
+            };
+            let payload = &payload;
+            let report = lights
+                .run(|light| async move { light.battery_settings_set(payload).await })
+                .await;
+            Outcome::from_report(&report)
+        }
+    };
+    Ok(outcome)
+}
+
+fn print(endpoint: &str, info: &BatteryInfo, settings: &BatterySettingsGet) {
+    let energy_saving = &settings.energy_saving;
+    println!("{}", endpoint);
+    println!("  level:          {}%", info.level.round());
+    println!("  status:         {}", info.status);
+    println!("  power source:   {}", info.power_source);
+    println!(
+        "  energy saving:  {}",
+        if energy_saving.enable { "on" } else { "off" }
+    );
+    println!(
+        "  below level:    {}%",
+        energy_saving.minimum_battery_level.round()
+    );
+    match energy_saving.adjust_brightness.enable {
+        true => println!(
+            "  brightness cap: {}%",
+            energy_saving.adjust_brightness.brightness.round()
+        ),
+        false => println!("  brightness cap: off"),
+    }
+    println!(
+        "  disable wifi:   {}",
+        if energy_saving.disable_wifi {
+            "on"
+        } else {
+            "off"
+        }
+    );
+    println!(
+        "  bypass:         {}",
+        if settings.bypass { "on" } else { "off" }
+    );
+}
@@ -1,14 +1,217 @@
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, bail};
 use directories::ProjectDirs;
+use futures::future::join_all;
+use open_ecc::{contracts::AccessoryInfoGet, identity::Identity, snapshot::Snapshot};
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::{fmt, fs};
 
-use crate::{Args, Commands};
+use crate::{Args, Commands, args::Selection, build_ecc, group, lights};
 
 #[derive(Serialize, Deserialize, Default)]
 pub(crate) struct AppConfig {
-    pub endpoints: Option<Vec<String>>,
+    /// Lights without a name, plain addresses in older configurations
+    pub endpoints: Option<Vec<LightConfig>>,
+    /// Added with `ecc lights add`, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub lights: BTreeMap<String, LightConfig>,
+    /// Names of the lights in each group
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, Vec<String>>,
+    /// Saved with `ecc scene save`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub scenes: BTreeMap<String, Snapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "LightEntry")]
+pub(crate) struct LightConfig {
+    /// Last known address
+    pub endpoint: String,
+    /// Last host name advertised over mDNS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Used to find the light again when its address changes
+    #[serde(default, skip_serializing_if = "Identity::is_empty")]
+    pub identity: Identity,
+}
+
+/// A light as written in the configuration, a table or just an address
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LightEntry {
+    Address(String),
+    Table {
+        endpoint: String,
+        #[serde(default)]
+        hostname: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(default)]
+        identity: Identity,
+    },
+}
+
+impl LightConfig {
+    // Constructors
+
+    pub fn new(endpoint: String, identity: Identity) -> Self {
+        Self {
+            endpoint,
+            hostname: None,
+            tags: Vec::new(),
+            identity,
+        }
+    }
+}
+
+impl From<LightEntry> for LightConfig {
+    fn from(value: LightEntry) -> Self {
+        match value {
+            LightEntry::Address(endpoint) => Self::new(endpoint, Identity::default()),
+            LightEntry::Table {
+                endpoint,
+                hostname,
+                tags,
+                identity,
+            } => Self {
+                endpoint,
+                hostname,
+                tags,
+                identity,
+            },
+        }
+    }
+}
+
+/// The configuration file couldn't be read or written, or doesn't have what was asked for.
+/// Exits with `EXIT_CONFIG_ERROR` wherever it happens.
+#[derive(Debug)]
+pub(crate) struct ConfigError(pub String);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl AppConfig {
+    // Public
+
+    /// Number of lights, named or not
+    pub fn len(&self) -> usize {
+        self.lights.len() + self.endpoints.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Save a light that was found on the network, with its serial number and MAC address.
+    /// A light saved before, named or not, follows the device to its new address,
+    /// others are added to the endpoints.
+    pub fn remember(
+        &mut self,
+        endpoint: String,
+        hostname: Option<String>,
+        info: &AccessoryInfoGet,
+    ) {
+        if let Some(light) = self.tracked_mut().find(|x| x.identity.matches(info)) {
+            light.endpoint = endpoint;
+            if hostname.is_some() {
+                light.hostname = hostname;
+            }
+            return;
+        }
+        let endpoints = self.endpoints.get_or_insert_with(Vec::new);
+        // Whatever was saved at this address, another device answers there now
+        endpoints.retain(|x| x.endpoint != endpoint);
+        endpoints.push(LightConfig {
+            hostname,
+            ..LightConfig::new(endpoint, Identity::from(info))
+        });
+    }
+
+    /// Saved light at an endpoint, named or not
+    pub fn tracked(&self, endpoint: &str) -> Option<&LightConfig> {
+        self.lights
+            .values()
+            .chain(self.endpoints.iter().flatten())
+            .find(|x| x.endpoint == endpoint)
+    }
+
+    /// Every saved light, named or not
+    pub fn tracked_mut(&mut self) -> impl Iterator<Item = &mut LightConfig> {
+        self.lights
+            .values_mut()
+            .chain(self.endpoints.iter_mut().flatten())
+    }
+
+    pub fn light(&self, name: &str) -> Result<&LightConfig> {
+        self.lights.get(name).ok_or_else(|| {
+            ConfigError(format!("No light named '{}', see `ecc lights list`", name)).into()
+        })
+    }
+
+    /// Endpoints of the selected lights, in the order given and without duplicates
+    pub fn select(&self, selection: &Selection) -> Result<Vec<String>> {
+        let mut endpoints = Vec::new();
+        if selection.is_all() {
+            let named = self.lights.values().map(|x| &x.endpoint);
+            let unnamed = self.endpoints.iter().flatten().map(|x| &x.endpoint);
+            for endpoint in named.chain(unnamed) {
+                push(&mut endpoints, endpoint);
+            }
+            if endpoints.is_empty() {
+                bail!(ConfigError(
+                    "No endpoints defined in the configuration\n\
+                    Please set endpoints using command: ecc endpoints\n\
+                    For example: ecc endpoints 192.168.0.50 192.168.0.51"
+                        .to_string()
+                ));
+            }
+            return Ok(endpoints);
+        }
+
+        for name in &selection.lights {
+            push(&mut endpoints, &self.light(name)?.endpoint);
+        }
+        for group in &selection.groups {
+            let names = self.groups.get(group).ok_or_else(|| {
+                ConfigError(format!("No group named '{}', see `ecc group list`", group))
+            })?;
+            for name in names {
+                push(&mut endpoints, &self.light(name)?.endpoint);
+            }
+        }
+        for tag in &selection.tags {
+            let tagged = self
+                .lights
+                .values()
+                .filter(|x| x.tags.contains(tag))
+                .collect::<Vec<_>>();
+            if tagged.is_empty() {
+                bail!(ConfigError(format!(
+                    "No lights tagged '{}', see `ecc lights list`",
+                    tag
+                )));
+            }
+            for light in tagged {
+                push(&mut endpoints, &light.endpoint);
+            }
+        }
+        if endpoints.is_empty() {
+            bail!(ConfigError("No lights selected".to_string()));
+        }
+        Ok(endpoints)
+    }
+}
+
+fn push(endpoints: &mut Vec<String>, endpoint: &str) {
+    if !endpoints.iter().any(|x| x == endpoint) {
+        endpoints.push(endpoint.to_string());
+    }
 }
 
 pub(crate) fn get_config_path() -> Result<PathBuf> {
@@ -24,9 +227,9 @@ pub(crate) fn get_config_path() -> Result<PathBuf> {
 pub(crate) fn load_config(config_path: &PathBuf) -> Result<AppConfig> {
     if config_path.exists() {
         let config_str = fs::read_to_string(config_path)
-            .with_context(|| format!("Failed to read {}", config_path.display()))?;
-        let config: AppConfig =
-            toml::from_str(&config_str).with_context(|| "Configuration file is malformed")?;
+            .with_context(|| ConfigError(format!("Failed to read {}", config_path.display())))?;
+        let config: AppConfig = toml::from_str(&config_str)
+            .with_context(|| ConfigError("Configuration file is malformed".to_string()))?;
         Ok(config)
     } else {
         Ok(AppConfig::default())
@@ -34,37 +237,131 @@ pub(crate) fn load_config(config_path: &PathBuf) -> Result<AppConfig> {
 }
 
 pub(crate) fn save_config(config: &AppConfig, config_path: &PathBuf) -> Result<()> {
-    let toml_str = toml::to_string(&config).context("Failed to serialize configuration")?;
-    fs::create_dir_all(
-        config_path
-            .parent()
-            .ok_or_else(|| anyhow!("Invalid config path"))?,
-    )?;
+    let toml_str = toml::to_string(&config)
+        .with_context(|| ConfigError("Failed to serialize configuration".to_string()))?;
+    let dir = config_path
+        .parent()
+        .ok_or_else(|| ConfigError("Invalid config path".to_string()))?;
+    fs::create_dir_all(dir)
+        .with_context(|| ConfigError(format!("Failed to create {}", dir.display())))?;
     fs::write(config_path, toml_str).with_context(|| {
-        format!(
+        ConfigError(format!(
             "Failed to write configuration file at {}",
             config_path.display()
-        )
+        ))
     })?;
     Ok(())
 }
 
-pub(crate) fn init(args: &Args) -> Result<Option<Vec<String>>> {
+pub(crate) async fn init(args: &Args) -> Result<Option<Vec<String>>> {
     if let Commands::Endpoints { endpoints } = &args.command {
         let config_path = get_config_path()?;
         let mut config = load_config(&config_path)?;
-        config.endpoints = Some(endpoints.clone());
+        let ecc = build_ecc()?;
+        let identities = join_all(endpoints.iter().map(|x| lights::identify(&ecc, x))).await;
+        config.endpoints = Some(
+            endpoints
+                .iter()
+                .zip(identities)
+                .map(|(endpoint, identity)| LightConfig::new(endpoint.clone(), identity))
+                .collect(),
+        );
         save_config(&config, &config_path)?;
         return Ok(None);
     }
+    if let Commands::Lights { command } = &args.command {
+        lights::run(command).await?;
+        return Ok(None);
+    }
+    if let Commands::Group { command } = &args.command {
+        group::run(command)?;
+        return Ok(None);
+    }
+    if let Commands::Discover { .. } | Commands::Scan { .. } = &args.command {
+        return Ok(None);
+    }
     let config_path = get_config_path()?;
     let config: AppConfig = load_config(&config_path)?;
-    let endpoints = config.endpoints.ok_or_else(|| {
-        anyhow!(
-            "No endpoints defined in the configuration\n\
-            Please set endpoints using command: ecc endpoints\n\
-            For example: ecc endpoints 192.168.0.50 192.168.0.51"
-        )
-    })?;
+    let endpoints = config.select(&args.selection)?;
     Ok(Some(endpoints))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AppConfig {
+        let light = |endpoint: &str, tags: &[&str]| LightConfig {
+            tags: tags.iter().map(|x| x.to_string()).collect(),
+            ..LightConfig::new(endpoint.to_string(), Identity::default())
+        };
+        AppConfig {
+            endpoints: Some(vec![light("10.0.0.9", &[]), light("10.0.0.1", &[])]),
+            lights: BTreeMap::from([
+                ("left".to_string(), light("10.0.0.1", &["desk", "key"])),
+                ("right".to_string(), light("10.0.0.2", &["desk"])),
+                ("fill".to_string(), light("10.0.0.3", &["key"])),
+            ]),
+            groups: BTreeMap::from([(
+                "studio".to_string(),
+                vec!["fill".to_string(), "left".to_string()],
+            )]),
+            scenes: BTreeMap::new(),
+        }
+    }
+
+    fn selection(lights: &[&str], groups: &[&str], tags: &[&str]) -> Selection {
+        let strings = |x: &[&str]| x.iter().map(|x| x.to_string()).collect();
+        Selection {
+            lights: strings(lights),
+            groups: strings(groups),
+            tags: strings(tags),
+            all: false,
+        }
+    }
+
+    #[test]
+    fn test_select_union() {
+        let config = config();
+        // Lights, then groups, then tags, each endpoint once
+        let endpoints = config
+            .select(&selection(&["right"], &["studio"], &["desk"]))
+            .unwrap();
+        assert_eq!(endpoints, vec!["10.0.0.2", "10.0.0.3", "10.0.0.1"]);
+        let endpoints = config
+            .select(&selection(&["left", "left"], &[], &["key"]))
+            .unwrap();
+        assert_eq!(endpoints, vec!["10.0.0.1", "10.0.0.3"]);
+    }
+
+    #[test]
+    fn test_select_all() {
+        let config = config();
+        // Named lights by name, then the endpoints without a name
+        let expected = vec!["10.0.0.3", "10.0.0.1", "10.0.0.2", "10.0.0.9"];
+        assert_eq!(config.select(&selection(&[], &[], &[])).unwrap(), expected);
+        let all = Selection {
+            all: true,
+            ..Default::default()
+        };
+        assert_eq!(config.select(&all).unwrap(), expected);
+        assert!(AppConfig::default().select(&all).is_err());
+    }
+
+    #[test]
+    fn test_select_unknown() {
+        let config = config();
+        let error = config
+            .select(&selection(&["left", "back"], &[], &[]))
+            .unwrap_err();
+        assert!(error.to_string().contains("'back'"));
+        let error = config
+            .select(&selection(&[], &["office"], &[]))
+            .unwrap_err();
+        assert!(error.to_string().contains("'office'"));
+        let error = config
+            .select(&selection(&[], &[], &["ceiling"]))
+            .unwrap_err();
+        assert!(error.to_string().contains("'ceiling'"));
+    }
+}
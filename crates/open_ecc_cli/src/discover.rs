@@ -0,0 +1,49 @@
+use crate::{
+    build_ecc,
+    config::{get_config_path, load_config, save_config},
+};
+use anyhow::Result;
+use open_ecc::discovery::Discovery;
+use std::time::Duration;
+
+pub(crate) async fn run(timeout: u64, save: bool) -> Result<()> {
+    let ecc = build_ecc()?;
+    let discovery = Discovery::new()?;
+    let lights = discovery
+        .discover(&ecc, Duration::from_secs(timeout))
+        .await?;
+
+    if lights.is_empty() {
+        println!("No lights found");
+        return Ok(());
+    }
+
+    for light in &lights {
+        let info = &light.accessory_info;
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            light.endpoint(),
+            light.hostname,
+            info.display_name,
+            info.product_name,
+            info.serial_number
+        );
+    }
+
+    if save {
+        let config_path = get_config_path()?;
+        let mut config = load_config(&config_path)?;
+        for light in &lights {
+            config.remember(
+                light.endpoint(),
+                Some(light.hostname.clone()),
+                &light.accessory_info,
+            );
+        }
+        let count = config.len();
+        save_config(&config, &config_path)?;
+        println!("Saved {} endpoint(s) to {}", count, config_path.display());
+    }
+
+    Ok(())
+}
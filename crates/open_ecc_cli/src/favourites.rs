@@ -0,0 +1,63 @@
+use crate::{args::FavouritesCommands, outcome::Outcome};
+use anyhow::Result;
+use open_ecc::{contracts::Favourite, light::LightGroup};
+
+pub(crate) async fn run(lights: &LightGroup<'_>, command: FavouritesCommands) -> Result<Outcome> {
+    let outcome = match command {
+        FavouritesCommands::List => {
+            let report = lights
+                .run(|light| async move { light.favourites_get().await })
+                .await;
+            for member in &report.results {
+                if let Ok(favourites) = &member.result {
+                    print(member.light.endpoint(), favourites);
+                }
+            }
+            Outcome::from_report(&report)
+        }
+        FavouritesCommands::Add { brightness, kelvin } => {
+            let favourite = Favourite {
+                brightness,
+                temperature: kelvin.into(),
+            };
+            let report = lights
+                .run(|light| async move { light.favourites_add(favourite).await })
+                .await;
+            Outcome::from_report(&report)
+        }
+        FavouritesCommands::Remove { index } => {
+            let report = lights
+                .run(|light| async move { light.favourites_remove(index).await })
+                .await;
+            Outcome::from_report(&report)
+        }
+        FavouritesCommands::Clear => {
+            let report = lights
+                .run(|light| async move { light.favourites_set(&[]).await })
+                .await;
+            Outcome::from_report(&report)
+        }
+        FavouritesCommands::AutoMode { lux } => {
+            let report = lights
+                .run(|light| async move { light.auto_mode_target_set(lux).await })
+                .await;
+            Outcome::from_report(&report)
+        }
+    };
+    Ok(outcome)
+}
+
+fn print(endpoint: &str, favourites: &[Favourite]) {
+    println!("{}", endpoint);
+    if favourites.is_empty() {
+        println!("  no favourites");
+    }
+    for (i, favourite) in favourites.iter().enumerate() {
+        println!(
+            "  {}: {} {}",
+            i,
+            favourite.brightness,
+            favourite.temperature.kelvin()
+        );
+    }
+}
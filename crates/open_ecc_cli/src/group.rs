@@ -0,0 +1,42 @@
+use crate::{
+    args::GroupCommands,
+    config::{ConfigError, get_config_path, load_config, save_config},
+};
+use anyhow::Result;
+
+pub(crate) fn run(command: &GroupCommands) -> Result<()> {
+    let config_path = get_config_path()?;
+    let mut config = load_config(&config_path)?;
+    match command {
+        GroupCommands::Add { name, lights } => {
+            for light in lights {
+                config.light(light)?;
+            }
+            let group = config.groups.entry(name.clone()).or_default();
+            for light in lights {
+                if !group.contains(light) {
+                    group.push(light.clone());
+                }
+            }
+        }
+        GroupCommands::Remove { name, lights } => {
+            let group = config
+                .groups
+                .get_mut(name)
+                .ok_or_else(|| ConfigError(format!("No group named '{}'", name)))?;
+            match lights.is_empty() {
+                true => {
+                    config.groups.remove(name);
+                }
+                false => group.retain(|x| !lights.contains(x)),
+            }
+        }
+        GroupCommands::List => {
+            for (name, lights) in &config.groups {
+                println!("{}\t{}", name, lights.join(" "));
+            }
+            return Ok(());
+        }
+    }
+    save_config(&config, &config_path)
+}
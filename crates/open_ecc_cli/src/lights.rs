@@ -0,0 +1,70 @@
+use crate::{
+    args::LightsCommands,
+    build_ecc,
+    config::{ConfigError, LightConfig, get_config_path, load_config, save_config},
+    resolve,
+};
+use anyhow::Result;
+use open_ecc::{ecc::Ecc, identity::Identity};
+
+pub(crate) async fn run(command: &LightsCommands) -> Result<()> {
+    let config_path = get_config_path()?;
+    let mut config = load_config(&config_path)?;
+    match command {
+        LightsCommands::Add {
+            name,
+            endpoint,
+            tags,
+        } => {
+            let identity = identify(&build_ecc()?, endpoint).await;
+            config.lights.insert(
+                name.clone(),
+                LightConfig {
+                    tags: tags.clone(),
+                    ..LightConfig::new(endpoint.clone(), identity)
+                },
+            );
+        }
+        LightsCommands::Remove { name } => {
+            if config.lights.remove(name).is_none() {
+                return Err(ConfigError(format!("No light named '{}'", name)).into());
+            }
+            for lights in config.groups.values_mut() {
+                lights.retain(|x| x != name);
+            }
+        }
+        LightsCommands::List => {
+            for (name, light) in &config.lights {
+                let identity = match light.identity.is_empty() {
+                    true => "-".to_string(),
+                    false => light.identity.to_string(),
+                };
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    name,
+                    light.endpoint,
+                    identity,
+                    light.tags.join(" ")
+                );
+            }
+            return Ok(());
+        }
+        LightsCommands::Resolve { names, scan } => {
+            resolve::lights(&mut config, names, *scan).await?;
+        }
+    }
+    save_config(&config, &config_path)
+}
+
+/// Serial number and MAC address of the device at `endpoint`, empty if it doesn't answer.
+/// Without them the light can't be found again if its address changes.
+pub(crate) async fn identify(ecc: &Ecc, endpoint: &str) -> Identity {
+    match ecc.accessory_info_get(endpoint).await {
+        Ok(info) => Identity::from(&info),
+        Err(e) => {
+            eprintln!("{}\t{}", endpoint, e);
+            eprintln!("Saved without a serial number, save it again once it is on");
+            Identity::default()
+        }
+    }
+}
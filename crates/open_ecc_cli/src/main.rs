@@ -1,50 +1,222 @@
-use crate::args::{Args, Commands};
+use crate::{
+    args::{Args, Commands, SceneCommands, SettingsCommands},
+    outcome::{EXIT_CONFIG_ERROR, EXIT_FAILURE, Outcome},
+};
 use anyhow::Result;
-use args::WifiSecurity;
+use args::{Easing, WifiSecurity};
 use clap::Parser;
-use config::init;
-use open_ecc::{contracts::WifiConfig, ecc::Ecc, light::Light};
+use config::{AppConfig, ConfigError, get_config_path, init, load_config, save_config};
+use futures::{StreamExt, stream};
+use open_ecc::{
+    contracts::WifiConfig,
+    ecc::Ecc,
+    light::{Light, LightGroup},
+    transition::Transition,
+};
+use std::{collections::BTreeMap, pin::pin, process::ExitCode, time::Duration};
+use tokio_util::sync::CancellationToken;
 
 mod args;
+mod backup;
+mod battery;
 mod config;
+mod discover;
+mod favourites;
+mod group;
+mod lights;
+mod outcome;
+mod resolve;
+mod scan;
+mod scene;
+mod settings;
+mod status;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
     let args = Args::parse();
-    let endpoints = init(&args)?;
-    let endpoints = match endpoints {
-        Some(e) => e,
-        None => return Ok(()),
+    if let Commands::Discover { timeout, save } = args.command {
+        return exit(
+            discover::run(timeout, save)
+                .await
+                .map(|_| Outcome::default()),
+        );
+    }
+    if let Commands::Scan {
+        network,
+        port,
+        concurrency,
+        timeout,
+        save,
+    } = args.command
+    {
+        return exit(
+            scan::run(network, port, concurrency, timeout, save)
+                .await
+                .map(|_| Outcome::default()),
+        );
+    }
+    let endpoints = match init(&args).await {
+        Ok(Some(e)) => e,
+        Ok(None) => return ExitCode::SUCCESS,
+        Err(e) => return exit(Err(e)),
     };
+    exit(run(args, endpoints).await)
+}
+
+/// Summarise the outcome of a command, or report why it couldn't run
+fn exit(result: Result<Outcome>) -> ExitCode {
+    match result {
+        Ok(outcome) => outcome.finish(),
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            match e.downcast_ref::<ConfigError>() {
+                Some(_) => ExitCode::from(EXIT_CONFIG_ERROR),
+                None => ExitCode::from(EXIT_FAILURE),
+            }
+        }
+    }
+}
+
+/// Run the command, then once more on the saved lights that failed as if they had moved
+/// and were found at a new address
+async fn run(args: Args, endpoints: Vec<String>) -> Result<Outcome> {
+    let ecc = build_ecc()?;
+    let config_path = get_config_path()?;
+    let config = load_config(&config_path)?;
+    let mut outcome = execute(&ecc, &config, &args, &endpoints, false).await?;
+    // Watching runs until interrupted, there is nothing left to retry
+    if let Commands::Watch { .. } = args.command {
+        return Ok(outcome);
+    }
+
+    // Read again, the command may have saved to it
+    let mut config = load_config(&config_path)?;
+    let moved = resolve::run(&ecc, &mut config, &outcome.moved()).await;
+    if moved.is_empty() {
+        return Ok(outcome);
+    }
+    save_config(&config, &config_path)?;
+    // Lights that swapped addresses are retried at each other's
+    let mut retry = Vec::new();
+    for (_, to) in &moved {
+        if !retry.contains(to) {
+            retry.push(to.clone());
+        }
+    }
+    let retried = execute(&ecc, &config, &args, &retry, true).await?;
+    outcome.retried(&moved, retried);
+    Ok(outcome)
+}
 
-    let ecc = Ecc::default();
-    let lights = endpoints.iter().map(|endpoint| Light::new(&ecc, endpoint));
+/// Run the command on `endpoints`, expecting the saved lights to answer as themselves.
+/// A `retry` adds to what the first run saved rather than replacing it.
+async fn execute(
+    ecc: &Ecc,
+    config: &AppConfig,
+    args: &Args,
+    endpoints: &[String],
+    retry: bool,
+) -> Result<Outcome> {
+    let lights = LightGroup::new(endpoints.iter().map(|endpoint| {
+        let mut light = Light::new(ecc, endpoint);
+        if let Some(saved) = config.tracked(endpoint) {
+            light = light.with_identity(&saved.identity);
+        }
+        match args.index {
+            Some(index) => light.channel(index),
+            None => light,
+        }
+    }));
+    // Capabilities are read once here rather than by every operation that checks
+    // for a feature, which also checks the identities. Either way a saved light
+    // is known to be itself before anything is sent to it.
+    let (lights, report) = match needs_capabilities(&args.command) {
+        true => lights.load_capabilities().await,
+        false => lights.verify_identities().await,
+    };
+    let mut skipped = Outcome::from_report(&report);
 
-    match args.command {
+    let outcome = match args.command.clone() {
         Commands::Brightness { value } => {
-            for light in lights {
-                _ = light.brightness_set(value).await;
-            }
+            Outcome::from_report(&lights.brightness_adjust(value).await)
         }
         Commands::Temperature { value } => {
-            for light in lights {
-                _ = light.temperature_set(value).await;
-            }
+            Outcome::from_report(&lights.temperature_adjust(value).await)
         }
-        Commands::Toggle => {
-            for light in lights {
-                _ = light.toggle().await;
+        Commands::Color { value } => Outcome::from_report(&lights.color_set(value).await),
+        Commands::Fade {
+            to,
+            kelvin,
+            over,
+            easing,
+        } => {
+            let mut transition = Transition::new(over).easing(match easing {
+                Easing::Linear => open_ecc::transition::Easing::Linear,
+                Easing::EaseIn => open_ecc::transition::Easing::EaseIn,
+                Easing::EaseOut => open_ecc::transition::Easing::EaseOut,
+                Easing::EaseInOut => open_ecc::transition::Easing::EaseInOut,
+                Easing::Perceptual => open_ecc::transition::Easing::Perceptual,
+            });
+            if let Some(value) = to {
+                transition = transition.brightness(value);
             }
-        }
-        Commands::On => {
-            for light in lights {
-                _ = light.on().await;
+            if let Some(value) = kelvin {
+                transition = transition.temperature(value);
             }
+            transition.validate()?;
+            let cancel = CancellationToken::new();
+            let guard = cancel.clone();
+            tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    guard.cancel();
+                }
+            });
+            Outcome::from_report(&lights.transition(&transition, &cancel).await)
+        }
+        Commands::Scene { command } => scene::run(&lights, command, retry).await?,
+        Commands::Backup { file } => backup::save(&lights, &file).await?,
+        Commands::Restore { file, from } => {
+            backup::restore(&lights, &file, from.as_deref()).await?
         }
-        Commands::Off => {
-            for light in lights {
-                _ = light.off().await;
+        Commands::Battery { command } => battery::run(&lights, command).await?,
+        Commands::Favourites { command } => favourites::run(&lights, command).await?,
+        Commands::Settings { command } => settings::run(&lights, command).await?,
+        Commands::Status { output } => status::status(&lights, output).await?,
+        Commands::Info { output } => status::info(&lights, output).await?,
+        Commands::Toggle => Outcome::from_report(&lights.toggle().await),
+        Commands::On => Outcome::from_report(&lights.on().await),
+        Commands::Off => Outcome::from_report(&lights.off().await),
+        Commands::Watch { interval } => {
+            let events = stream::select_all(lights.lights().iter().map(|light| {
+                Box::pin(
+                    light
+                        .watch(interval)
+                        .map(move |event| (light.endpoint(), event)),
+                )
+            }));
+            // Runs until interrupted, then reports the lights that failed to answer
+            let mut events = pin!(events.take_until(tokio::signal::ctrl_c()));
+            let mut errors = BTreeMap::new();
+            while let Some((endpoint, event)) = events.next().await {
+                match event {
+                    Ok(event) => println!("{}\t{}", endpoint, event),
+                    Err(e) => {
+                        eprintln!("{}\t{}", endpoint, e);
+                        errors.entry(endpoint).or_insert(e);
+                    }
+                }
             }
+            let mut outcome = Outcome::default();
+            for light in lights.lights() {
+                match errors.get(light.endpoint()) {
+                    Some(e) => outcome.failure(light.endpoint(), e),
+                    None => outcome.success(light.endpoint()),
+                }
+            }
+            outcome
         }
         Commands::Wifi {
             ssid,
@@ -61,12 +233,49 @@ async fn main() -> Result<()> {
                 },
                 channel,
             };
-            for endpoint in endpoints {
-                _ = ecc.wifi_config(&endpoint, &wifi_config).await;
-            }
+            let wifi_config = &wifi_config;
+            let report = lights
+                .run(|light| async move { ecc.wifi_config(light.endpoint(), wifi_config).await })
+                .await;
+            Outcome::from_report(&report)
         }
-        _ => {}
+        Commands::Endpoints { .. }
+        | Commands::Discover { .. }
+        | Commands::Scan { .. }
+        | Commands::Lights { .. }
+        | Commands::Group { .. } => Outcome::default(),
+    };
+
+    skipped.extend(outcome);
+    Ok(skipped)
+}
+
+/// Whether the command checks what each light supports
+fn needs_capabilities(command: &Commands) -> bool {
+    match command {
+        Commands::Temperature { .. } | Commands::Color { .. } | Commands::Battery { .. } => true,
+        Commands::Fade { kelvin, .. } => kelvin.is_some(),
+        Commands::Scene { command } => match command {
+            SceneCommands::Set { .. } => true,
+            SceneCommands::Load { fade, .. } => fade.is_some(),
+            _ => false,
+        },
+        Commands::Settings { command } => matches!(
+            command,
+            SettingsCommands::Set {
+                power_on_kelvin: Some(_),
+                ..
+            }
+        ),
+        _ => false,
     }
+}
 
-    Ok(())
+pub(crate) fn build_ecc() -> Result<Ecc> {
+    let ecc = Ecc::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("ecc/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    Ok(ecc)
 }
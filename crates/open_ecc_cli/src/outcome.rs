@@ -0,0 +1,192 @@
+use open_ecc::{Error, light::GroupReport};
+use std::{collections::BTreeSet, process::ExitCode};
+
+/// A command failed before reaching any light
+pub(crate) const EXIT_FAILURE: u8 = 1;
+/// The command failed on some of the lights
+pub(crate) const EXIT_PARTIAL_FAILURE: u8 = 2;
+/// The command failed on every light
+pub(crate) const EXIT_ALL_FAILED: u8 = 3;
+/// The configuration is missing or malformed
+pub(crate) const EXIT_CONFIG_ERROR: u8 = 4;
+
+/// Results of a command on each endpoint, summarised once it is done.
+/// An endpoint counts as failed if any request to it failed.
+#[derive(Default)]
+pub(crate) struct Outcome {
+    succeeded: BTreeSet<String>,
+    failed: Vec<Failure>,
+}
+
+struct Failure {
+    endpoint: String,
+    reason: String,
+    /// The light didn't answer, or another device did, as if the light had moved
+    moved: bool,
+}
+
+impl Outcome {
+    // Constructors
+
+    pub fn from_report<T>(report: &GroupReport<'_, T>) -> Self {
+        let mut outcome = Self::default();
+        outcome.add(report);
+        outcome
+    }
+
+    // Public
+
+    pub fn add<T>(&mut self, report: &GroupReport<'_, T>) {
+        for member in &report.results {
+            match &member.result {
+                Ok(_) => self.success(member.light.endpoint()),
+                Err(e) => self.failure(member.light.endpoint(), e),
+            }
+        }
+    }
+
+    pub fn extend(&mut self, other: Outcome) {
+        self.succeeded.extend(other.succeeded);
+        self.failed.extend(other.failed);
+    }
+
+    pub fn success(&mut self, endpoint: &str) {
+        self.succeeded.insert(endpoint.to_string());
+    }
+
+    pub fn failure(&mut self, endpoint: &str, error: &Error) {
+        self.failed.push(Failure {
+            endpoint: endpoint.to_string(),
+            reason: reason(error),
+            moved: error.is_offline() || matches!(error, Error::WrongDevice { .. }),
+        });
+    }
+
+    /// Endpoints that failed as if their light had moved
+    pub fn moved(&self) -> Vec<String> {
+        let mut endpoints = Vec::<String>::new();
+        for failure in self.failed.iter().filter(|x| x.moved) {
+            if !endpoints.contains(&failure.endpoint) {
+                endpoints.push(failure.endpoint.clone());
+            }
+        }
+        endpoints
+    }
+
+    /// Add the results of a retry of the lights that `moved`, given as their old and
+    /// new endpoints. The results at an old endpoint are dropped only once the retry
+    /// succeeded at the new one, lights that failed again stay failed.
+    pub fn retried(&mut self, moved: &[(String, String)], retry: Outcome) {
+        for (from, to) in moved {
+            if retry.is_success(to) {
+                self.succeeded.remove(from);
+                self.failed.retain(|x| x.endpoint != *from);
+            }
+        }
+        self.extend(retry);
+    }
+
+    /// Print the failures, if any, and the matching exit code
+    pub fn finish(self) -> ExitCode {
+        if self.failed.is_empty() {
+            return ExitCode::SUCCESS;
+        }
+        for failure in &self.failed {
+            eprintln!("{}\t{}", failure.endpoint, failure.reason);
+        }
+        let failed = self
+            .failed
+            .iter()
+            .map(|x| x.endpoint.clone())
+            .collect::<BTreeSet<_>>();
+        let total = self.succeeded.union(&failed).count();
+        eprintln!("Failed on {} of {} endpoint(s)", failed.len(), total);
+        match self.succeeded.is_subset(&failed) {
+            true => ExitCode::from(EXIT_ALL_FAILED),
+            false => ExitCode::from(EXIT_PARTIAL_FAILURE),
+        }
+    }
+
+    // Private
+
+    /// Whether the command succeeded on `endpoint`
+    fn is_success(&self, endpoint: &str) -> bool {
+        self.succeeded.contains(endpoint) && !self.failed.iter().any(|x| x.endpoint == endpoint)
+    }
+}
+
+/// The error and its causes, such as `Request failed: ... Connection refused`
+fn reason(error: &dyn std::error::Error) -> String {
+    let mut reason = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let text = cause.to_string();
+        if !reason.contains(&text) {
+            reason.push_str(": ");
+            reason.push_str(&text);
+        }
+        source = cause.source();
+    }
+    reason
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use open_ecc::identity::Identity;
+
+    fn identity(serial_number: &str) -> Identity {
+        Identity {
+            serial_number: serial_number.to_string(),
+            mac_address: String::new(),
+        }
+    }
+
+    /// Two lights that swapped addresses, each answering at the other's
+    fn swapped() -> (Outcome, Vec<(String, String)>) {
+        let mut outcome = Outcome::default();
+        for (endpoint, expected, found) in [("10.0.0.1", "BW1", "BW2"), ("10.0.0.2", "BW2", "BW1")]
+        {
+            let error = Error::WrongDevice {
+                expected: identity(expected),
+                found: identity(found),
+            };
+            outcome.failure(endpoint, &error);
+        }
+        let moved = vec![
+            ("10.0.0.1".to_string(), "10.0.0.2".to_string()),
+            ("10.0.0.2".to_string(), "10.0.0.1".to_string()),
+        ];
+        (outcome, moved)
+    }
+
+    #[test]
+    fn test_retried_swapped() {
+        let (mut outcome, moved) = swapped();
+        assert_eq!(outcome.moved(), vec!["10.0.0.1", "10.0.0.2"]);
+
+        let mut retry = Outcome::default();
+        retry.success("10.0.0.2");
+        retry.success("10.0.0.1");
+        outcome.retried(&moved, retry);
+        assert!(outcome.failed.is_empty());
+        assert_eq!(outcome.succeeded.len(), 2);
+    }
+
+    #[test]
+    fn test_retried_failed_again() {
+        let (mut outcome, moved) = swapped();
+
+        // The light that moved to 10.0.0.2 failed there too, the other one is done
+        let mut retry = Outcome::default();
+        retry.success("10.0.0.1");
+        retry.failure("10.0.0.2", &Error::Unsupported("color"));
+        outcome.retried(&moved, retry);
+        let failed = outcome
+            .failed
+            .iter()
+            .map(|x| x.endpoint.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(failed, vec!["10.0.0.1", "10.0.0.2"]);
+    }
+}
@@ -0,0 +1,322 @@
+use crate::{build_ecc, config::AppConfig};
+use anyhow::Result;
+use futures::future::join_all;
+use ipnet::{IpNet, Ipv4Net};
+use open_ecc::{
+    discovery::Discovery,
+    ecc::{DEFAULT_PORT, Ecc},
+    identity::Identity,
+    scan::Scanner,
+};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+/// How long to browse for lights that moved
+const BROWSE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A saved light that doesn't answer as itself at its last known address
+struct Stale {
+    /// Name of the light, or its address if it has none
+    label: String,
+    endpoint: String,
+    identity: Identity,
+    hostname: Option<String>,
+    /// New endpoint and host name
+    found: Option<(String, Option<String>)>,
+}
+
+impl Stale {
+    // Constructors
+
+    /// The saved light at `endpoint`, if it has an identity to look for
+    fn new(config: &AppConfig, endpoint: &str) -> Option<Self> {
+        let light = config.tracked(endpoint)?;
+        if light.identity.is_empty() {
+            return None;
+        }
+        let label = config
+            .lights
+            .iter()
+            .find(|(_, x)| x.endpoint == endpoint)
+            .map_or(endpoint, |(name, _)| name);
+        Some(Self {
+            label: label.to_string(),
+            endpoint: endpoint.to_string(),
+            identity: light.identity.clone(),
+            hostname: light.hostname.clone(),
+            found: None,
+        })
+    }
+}
+
+/// Point the saved entries of the lights that were found, and the scenes they are in,
+/// to where they were found. Every entry is moved at most once, so that lights which
+/// swapped addresses aren't moved back.
+fn update(config: &mut AppConfig, stale: &[Stale]) {
+    let found = stale
+        .iter()
+        .filter_map(|light| light.found.as_ref().map(|found| (light, found)))
+        .collect::<Vec<_>>();
+    for light in config.tracked_mut() {
+        let moved = found
+            .iter()
+            .find(|(x, _)| x.endpoint == light.endpoint && x.identity == light.identity);
+        if let Some((_, (endpoint, hostname))) = moved {
+            light.endpoint = endpoint.clone();
+            light.hostname = hostname.clone();
+        }
+    }
+    for device in config
+        .scenes
+        .values_mut()
+        .flat_map(|x| x.devices.iter_mut())
+    {
+        if let Some((_, (endpoint, _))) = found.iter().find(|(x, _)| x.endpoint == device.endpoint)
+        {
+            device.endpoint = endpoint.clone();
+        }
+    }
+}
+
+/// Look for the saved lights at `endpoints`, which failed as if they had moved,
+/// by host name and over mDNS. The config follows the lights that are found,
+/// returned as their old and new endpoints.
+pub(crate) async fn run(
+    ecc: &Ecc,
+    config: &mut AppConfig,
+    endpoints: &[String],
+) -> Vec<(String, String)> {
+    let mut stale = endpoints
+        .iter()
+        .filter_map(|x| Stale::new(config, x))
+        .collect::<Vec<_>>();
+    if stale.is_empty() {
+        return Vec::new();
+    }
+    locate(ecc, &mut stale, false).await;
+
+    let mut moved = Vec::new();
+    for light in &stale {
+        if let Some((endpoint, _)) = &light.found {
+            eprintln!(
+                "{} moved from {} to {}",
+                light.label, light.endpoint, endpoint
+            );
+            moved.push((light.endpoint.clone(), endpoint.clone()));
+        }
+    }
+    update(config, &stale);
+    moved
+}
+
+/// Check the given named lights, or every saved light, and look for those that moved.
+/// With `scan`, also sweep the network each light was last seen on, for when mDNS is filtered.
+pub(crate) async fn lights(config: &mut AppConfig, names: &[String], scan: bool) -> Result<()> {
+    let mut endpoints = Vec::<String>::new();
+    for name in names {
+        endpoints.push(config.light(name)?.endpoint.clone());
+    }
+    if names.is_empty() {
+        let saved = config
+            .lights
+            .values()
+            .chain(config.endpoints.iter().flatten());
+        endpoints.extend(saved.map(|x| x.endpoint.clone()));
+    }
+    let mut unique = Vec::<String>::new();
+    for endpoint in endpoints {
+        if !unique.contains(&endpoint) {
+            unique.push(endpoint);
+        }
+    }
+
+    let ecc = build_ecc()?;
+    let saved = unique
+        .iter()
+        .filter_map(|x| Stale::new(config, x))
+        .collect::<Vec<_>>();
+    let checks = join_all(saved.iter().map(|x| verify(&ecc, &x.endpoint, &x.identity))).await;
+    let (mut stale, errors): (Vec<_>, Vec<_>) = saved
+        .into_iter()
+        .zip(checks)
+        .filter_map(|(light, check)| check.err().map(|error| (light, error)))
+        .unzip();
+    locate(&ecc, &mut stale, scan).await;
+
+    for (light, error) in stale.iter().zip(errors) {
+        match &light.found {
+            Some((endpoint, _)) => {
+                println!(
+                    "{} moved from {} to {}",
+                    light.label, light.endpoint, endpoint
+                );
+            }
+            None => eprintln!("{}\t{}\t{}", light.label, light.endpoint, error),
+        }
+    }
+    update(config, &stale);
+    Ok(())
+}
+
+/// Look for stale lights by host name, then over mDNS, then with `scan` on their last network
+async fn locate(ecc: &Ecc, stale: &mut [Stale], scan: bool) {
+    // The host name may still resolve when mDNS does, even if browsing doesn't
+    for light in stale.iter_mut() {
+        if let Some(hostname) = &light.hostname {
+            light.found = by_hostname(ecc, hostname, port(&light.endpoint), &light.identity)
+                .await
+                .map(|x| (x, Some(hostname.clone())));
+        }
+    }
+
+    let missing = stale
+        .iter()
+        .filter(|x| x.found.is_none())
+        .map(|x| x.identity.clone())
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        let discovered = match Discovery::new() {
+            Ok(discovery) => discovery
+                .locate(ecc, &missing, BROWSE_TIMEOUT)
+                .await
+                .unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+        for light in stale.iter_mut().filter(|x| x.found.is_none()) {
+            light.found = discovered
+                .iter()
+                .find(|x| light.identity.matches(&x.accessory_info))
+                .map(|x| (x.endpoint(), Some(x.hostname.clone())));
+        }
+    }
+    if !scan {
+        return;
+    }
+
+    let mut sweeps = Vec::<(IpNet, u16)>::new();
+    for light in stale.iter().filter(|x| x.found.is_none()) {
+        if let Some(network) = network(&light.endpoint) {
+            let sweep = (network, port(&light.endpoint));
+            if !sweeps.contains(&sweep) {
+                sweeps.push(sweep);
+            }
+        }
+    }
+    for (network, port) in sweeps {
+        let scanned = Scanner::new(ecc)
+            .port(port)
+            .scan(network)
+            .await
+            .unwrap_or_default();
+        for light in stale.iter_mut().filter(|x| x.found.is_none()) {
+            let hostname = light.hostname.clone();
+            light.found = scanned
+                .iter()
+                .find(|x| light.identity.matches(&x.accessory_info))
+                .map(|x| (x.endpoint(), hostname));
+        }
+    }
+}
+
+/// `Ok` if the device at `endpoint` is the one with `identity`
+async fn verify(ecc: &Ecc, endpoint: &str, identity: &Identity) -> open_ecc::Result<()> {
+    let info = ecc.accessory_info_get(endpoint).await?;
+    identity.verify(&info)
+}
+
+/// First address of `hostname` where the device with `identity` answers
+async fn by_hostname(ecc: &Ecc, hostname: &str, port: u16, identity: &Identity) -> Option<String> {
+    let addresses = tokio::net::lookup_host((hostname, port)).await.ok()?;
+    for address in addresses {
+        let endpoint = address.to_string();
+        if verify(ecc, &endpoint, identity).await.is_ok() {
+            return Some(endpoint);
+        }
+    }
+    None
+}
+
+/// The /24 around an IPv4 endpoint, IPv6 networks being too large to sweep
+fn network(endpoint: &str) -> Option<IpNet> {
+    let address = endpoint
+        .parse::<SocketAddr>()
+        .map(|x| x.ip())
+        .or_else(|_| endpoint.parse::<IpAddr>())
+        .ok()?;
+    match address {
+        IpAddr::V4(address) => Ipv4Net::new(address, 24).ok().map(|x| x.trunc().into()),
+        IpAddr::V6(_) => None,
+    }
+}
+
+/// Port of an endpoint, or the default
+fn port(endpoint: &str) -> u16 {
+    match endpoint.parse::<SocketAddr>() {
+        Ok(address) => address.port(),
+        Err(_) => endpoint
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse().ok())
+            .unwrap_or(DEFAULT_PORT),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LightConfig;
+    use open_ecc::snapshot::{DeviceSnapshot, Snapshot};
+    use std::collections::BTreeMap;
+
+    fn identity(serial_number: &str) -> Identity {
+        Identity {
+            serial_number: serial_number.to_string(),
+            mac_address: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_update_swapped() {
+        let device = |endpoint: &str| DeviceSnapshot {
+            endpoint: endpoint.to_string(),
+            ..Default::default()
+        };
+        let mut config = AppConfig {
+            lights: BTreeMap::from([
+                (
+                    "left".to_string(),
+                    LightConfig::new("10.0.0.1".to_string(), identity("BW1")),
+                ),
+                (
+                    "right".to_string(),
+                    LightConfig::new("10.0.0.2".to_string(), identity("BW2")),
+                ),
+            ]),
+            scenes: BTreeMap::from([(
+                "evening".to_string(),
+                Snapshot {
+                    devices: vec![device("10.0.0.1"), device("10.0.0.2")],
+                },
+            )]),
+            ..Default::default()
+        };
+        let mut stale = ["10.0.0.1", "10.0.0.2"]
+            .into_iter()
+            .filter_map(|x| Stale::new(&config, x))
+            .collect::<Vec<_>>();
+        stale[0].found = Some(("10.0.0.2".to_string(), None));
+        stale[1].found = Some(("10.0.0.1".to_string(), None));
+
+        // Each entry follows its light once rather than being moved back
+        update(&mut config, &stale);
+        assert_eq!(config.lights["left"].endpoint, "10.0.0.2");
+        assert_eq!(config.lights["right"].endpoint, "10.0.0.1");
+        let endpoints = config.scenes["evening"]
+            .devices
+            .iter()
+            .map(|x| x.endpoint.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(endpoints, vec!["10.0.0.2", "10.0.0.1"]);
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{
+    build_ecc,
+    config::{get_config_path, load_config, save_config},
+};
+use anyhow::Result;
+use ipnet::IpNet;
+use open_ecc::scan::Scanner;
+use std::time::Duration;
+
+pub(crate) async fn run(
+    network: IpNet,
+    port: u16,
+    concurrency: usize,
+    timeout: Duration,
+    save: bool,
+) -> Result<()> {
+    let ecc = build_ecc()?;
+    let lights = Scanner::new(&ecc)
+        .port(port)
+        .concurrency(concurrency)
+        .timeout(timeout)
+        .scan(network)
+        .await?;
+
+    if lights.is_empty() {
+        println!("No lights found in {}", network);
+        return Ok(());
+    }
+
+    for light in &lights {
+        let info = &light.accessory_info;
+        println!(
+            "{}\t{}\t{}\t{}",
+            light.endpoint(),
+            info.display_name,
+            info.product_name,
+            info.serial_number
+        );
+    }
+
+    if save {
+        let config_path = get_config_path()?;
+        let mut config = load_config(&config_path)?;
+        for light in &lights {
+            config.remember(light.endpoint(), None, &light.accessory_info);
+        }
+        let count = config.len();
+        save_config(&config, &config_path)?;
+        println!("Saved {} endpoint(s) to {}", count, config_path.display());
+    }
+
+    Ok(())
+}
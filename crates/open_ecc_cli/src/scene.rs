@@ -0,0 +1,86 @@
+use crate::{
+    args::SceneCommands,
+    config::{ConfigError, get_config_path, load_config, save_config},
+    outcome::Outcome,
+};
+use anyhow::{Context, Result};
+use open_ecc::{contracts::Scene, light::LightGroup, snapshot::Snapshot};
+use std::{fs, path::Path};
+
+/// Run a scene command, with `merge` adding the lights saved to a scene rather than
+/// replacing it, for lights retried at a new address
+pub(crate) async fn run(
+    lights: &LightGroup<'_>,
+    command: SceneCommands,
+    merge: bool,
+) -> Result<Outcome> {
+    let outcome = match command {
+        SceneCommands::Set { file } => {
+            let scene = load(&file)?;
+            Outcome::from_report(&lights.scene_set(&scene).await)
+        }
+        SceneCommands::Save { name, settings } => {
+            let (snapshot, report) = Snapshot::capture(lights, settings).await;
+            // Saved with the lights that could be read, the others are reported
+            if !snapshot.devices.is_empty() {
+                let config_path = get_config_path()?;
+                let mut config = load_config(&config_path)?;
+                match (merge, config.scenes.get_mut(&name)) {
+                    (true, Some(saved)) => {
+                        saved
+                            .devices
+                            .retain(|x| !snapshot.devices.iter().any(|y| y.endpoint == x.endpoint));
+                        saved.devices.extend(snapshot.devices);
+                    }
+                    _ => {
+                        config.scenes.insert(name, snapshot);
+                    }
+                }
+                save_config(&config, &config_path)?;
+            }
+            Outcome::from_report(&report)
+        }
+        SceneCommands::Load { name, fade } => {
+            let config = load_config(&get_config_path()?)?;
+            let snapshot = config.scenes.get(&name).ok_or_else(|| {
+                ConfigError(format!("No scene named '{}', see `ecc scene list`", name))
+            })?;
+            Outcome::from_report(&snapshot.apply(lights, fade).await)
+        }
+        SceneCommands::List => {
+            let config = load_config(&get_config_path()?)?;
+            for (name, snapshot) in &config.scenes {
+                let endpoints = snapshot
+                    .devices
+                    .iter()
+                    .map(|x| x.endpoint.as_str())
+                    .collect::<Vec<_>>();
+                println!("{}\t{}", name, endpoints.join(" "));
+            }
+            Outcome::default()
+        }
+        SceneCommands::Remove { name } => {
+            let config_path = get_config_path()?;
+            let mut config = load_config(&config_path)?;
+            if config.scenes.remove(&name).is_none() {
+                return Err(ConfigError(format!("No scene named '{}'", name)).into());
+            }
+            save_config(&config, &config_path)?;
+            Outcome::default()
+        }
+    };
+    Ok(outcome)
+}
+
+/// Load a scene from a TOML file, or JSON for any other extension
+pub(crate) fn load(path: &Path) -> Result<Scene> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let scene = match path.extension().and_then(|x| x.to_str()) {
+        Some("toml") => toml::from_str(&contents)
+            .with_context(|| format!("Scene file {} is malformed", path.display()))?,
+        _ => serde_json::from_str(&contents)
+            .with_context(|| format!("Scene file {} is malformed", path.display()))?,
+    };
+    Ok(scene)
+}
@@ -0,0 +1,92 @@
+use crate::{
+    args::{PowerOnBehavior, SettingsCommands},
+    outcome::Outcome,
+};
+use anyhow::{Context, Result, bail};
+use open_ecc::{
+    contracts::{LightsSettingsGet, LightsSettingsPut},
+    light::LightGroup,
+};
+use std::time::Duration;
+
+pub(crate) async fn run(lights: &LightGroup<'_>, command: SettingsCommands) -> Result<Outcome> {
+    let mut outcome = Outcome::default();
+    match command {
+        SettingsCommands::Get => {
+            let report = lights.settings_get().await;
+            for member in &report.results {
+                if let Ok(settings) = &member.result {
+                    print(member.light.endpoint(), settings);
+                }
+            }
+            outcome.add(&report);
+        }
+        SettingsCommands::Set {
+            power_on,
+            power_on_brightness,
+            power_on_kelvin,
+            switch_on,
+            switch_off,
+            color_change,
+        } => {
+            let payload = LightsSettingsPut {
+                power_on_behavior: power_on.map(|x| match x {
+                    PowerOnBehavior::Off => open_ecc::contracts::PowerOnBehavior::Off,
+                    PowerOnBehavior::On => open_ecc::contracts::PowerOnBehavior::On,
+                }),
+                power_on_brightness,
+                // Checked against the range of each model below
+                power_on_temperature: None,
+                switch_on_duration_ms: switch_on.map(duration_ms).transpose()?,
+                switch_off_duration_ms: switch_off.map(duration_ms).transpose()?,
+                color_change_duration_ms: color_change.map(duration_ms).transpose()?,
+                remote_control: None,
+            };
+            let payload_given = payload.power_on_behavior.is_some()
+                || payload.power_on_brightness.is_some()
+                || payload.switch_on_duration_ms.is_some()
+                || payload.switch_off_duration_ms.is_some()
+                || payload.color_change_duration_ms.is_some();
+            if !payload_given && power_on_kelvin.is_none() {
+                bail!("No settings given, see `ecc settings set --help`");
+            }
+            if payload_given {
+                outcome.add(&lights.settings_set(&payload).await);
+            }
+            if let Some(value) = power_on_kelvin {
+                let report = lights
+                    .run(|light| async move { light.power_on_temperature_set(value).await })
+                    .await;
+                outcome.add(&report);
+            }
+        }
+    }
+    Ok(outcome)
+}
+
+fn print(endpoint: &str, settings: &LightsSettingsGet) {
+    println!("{}", endpoint);
+    println!("  power on:             {}", settings.power_on_behavior);
+    println!("  power on brightness:  {}", settings.power_on_brightness);
+    println!(
+        "  power on temperature: {}",
+        settings.power_on_temperature.kelvin()
+    );
+    println!(
+        "  switch on:            {}ms",
+        settings.switch_on_duration_ms
+    );
+    println!(
+        "  switch off:           {}ms",
+        settings.switch_off_duration_ms
+    );
+    println!(
+        "  colour change:        {}ms",
+        settings.color_change_duration_ms
+    );
+}
+
+fn duration_ms(value: Duration) -> Result<u16> {
+    u16::try_from(value.as_millis())
+        .with_context(|| format!("Duration of {}ms is too long", value.as_millis()))
+}
@@ -0,0 +1,197 @@
+use crate::{args::Output, outcome::Outcome};
+use anyhow::Result;
+use open_ecc::light::LightGroup;
+use serde::Serialize;
+
+/// What is printed for JSON and YAML, endpoints that failed are listed under `errors`
+#[derive(Serialize)]
+struct Report<T> {
+    results: Vec<T>,
+    errors: Vec<ErrorRow>,
+}
+
+#[derive(Serialize)]
+struct ErrorRow {
+    endpoint: String,
+    error: String,
+}
+
+#[derive(Serialize)]
+struct StatusRow {
+    endpoint: String,
+    index: usize,
+    on: bool,
+    brightness: u8,
+    /// Kelvin, absent in colour mode
+    temperature: Option<u16>,
+    hue: Option<f32>,
+    saturation: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct InfoRow {
+    endpoint: String,
+    product: String,
+    firmware: String,
+    serial_number: String,
+    mac_address: String,
+    display_name: String,
+    ssid: String,
+    rssi: i8,
+    frequency_mhz: u16,
+}
+
+pub(crate) async fn status(lights: &LightGroup<'_>, output: Output) -> Result<Outcome> {
+    let report = lights
+        .run(|light| async move {
+            let lights = light.lights_get().await?;
+            let rows = lights
+                .lights
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| light.index().is_none_or(|x| x == *i))
+                .map(|(index, x)| StatusRow {
+                    endpoint: light.endpoint().to_string(),
+                    index,
+                    on: x.on,
+                    brightness: x.brightness.get(),
+                    temperature: x.temperature.map(|x| x.kelvin().get()),
+                    hue: x.hue,
+                    saturation: x.saturation,
+                })
+                .collect::<Vec<_>>();
+            Ok(rows)
+        })
+        .await;
+    let outcome = Outcome::from_report(&report);
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for member in report.results {
+        match member.result {
+            Ok(x) => rows.extend(x),
+            Err(e) => errors.push(ErrorRow {
+                endpoint: member.light.endpoint().to_string(),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    print(
+        output,
+        Report {
+            results: rows,
+            errors,
+        },
+        &["ENDPOINT", "LIGHT", "POWER", "BRIGHTNESS", "TEMPERATURE"],
+        |x| {
+            vec![
+                x.endpoint.clone(),
+                x.index.to_string(),
+                if x.on { "on" } else { "off" }.to_string(),
+                format!("{}%", x.brightness),
+                match (x.temperature, x.hue, x.saturation) {
+                    (Some(kelvin), _, _) => format!("{}K", kelvin),
+                    (None, Some(hue), Some(saturation)) => {
+                        format!("hue {} sat {}", hue.round(), saturation.round())
+                    }
+                    _ => "-".to_string(),
+                },
+            ]
+        },
+    )?;
+    Ok(outcome)
+}
+
+pub(crate) async fn info(lights: &LightGroup<'_>, output: Output) -> Result<Outcome> {
+    let report = lights
+        .run(|light| async move { light.accessory_info_get().await })
+        .await;
+    let outcome = Outcome::from_report(&report);
+
+    let mut rows = Vec::<InfoRow>::new();
+    let mut errors = Vec::new();
+    for member in report.results {
+        let endpoint = member.light.endpoint().to_string();
+        match member.result {
+            // Channels of the same device share their info
+            Ok(_) if rows.iter().any(|x| x.endpoint == endpoint) => {}
+            Ok(x) => rows.push(InfoRow {
+                endpoint,
+                product: x.product_name,
+                firmware: format!("{} ({})", x.firmware_version, x.firmware_build_number),
+                serial_number: x.serial_number,
+                mac_address: x.mac_address,
+                display_name: x.display_name,
+                ssid: x.wifi_info.ssid,
+                rssi: x.wifi_info.rssi,
+                frequency_mhz: x.wifi_info.frequency_mhz,
+            }),
+            Err(e) => errors.push(ErrorRow {
+                endpoint,
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    print(
+        output,
+        Report {
+            results: rows,
+            errors,
+        },
+        &[
+            "ENDPOINT", "PRODUCT", "FIRMWARE", "SERIAL", "MAC", "NAME", "SSID", "RSSI", "MHZ",
+        ],
+        |x| {
+            vec![
+                x.endpoint.clone(),
+                x.product.clone(),
+                x.firmware.clone(),
+                x.serial_number.clone(),
+                x.mac_address.clone(),
+                x.display_name.clone(),
+                x.ssid.clone(),
+                x.rssi.to_string(),
+                x.frequency_mhz.to_string(),
+            ]
+        },
+    )?;
+    Ok(outcome)
+}
+
+/// Print the report as JSON or YAML, or the rows as a table
+fn print<T: Serialize>(
+    output: Output,
+    report: Report<T>,
+    headers: &[&str],
+    cells: impl Fn(&T) -> Vec<String>,
+) -> Result<()> {
+    match output {
+        Output::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        Output::Yaml => print!("{}", serde_yaml_ng::to_string(&report)?),
+        Output::Table => {
+            let mut table = vec![headers.iter().map(|x| x.to_string()).collect::<Vec<_>>()];
+            table.extend(report.results.iter().map(cells));
+            let widths = (0..headers.len())
+                .map(|i| {
+                    table
+                        .iter()
+                        .map(|row| row[i].chars().count())
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect::<Vec<_>>();
+            for row in &table {
+                let line = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                println!("{}", line.trim_end());
+            }
+        }
+    }
+    Ok(())
+}
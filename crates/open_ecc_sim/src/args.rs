@@ -0,0 +1,47 @@
+use open_ecc_sim::FaultRule;
+use std::net::IpAddr;
+
+#[derive(clap::Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub(crate) struct Args {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    pub bind: IpAddr,
+    /// Port to listen on
+    #[arg(long, default_value_t = 9123)]
+    pub port: u16,
+    /// Product name reported in the accessory info
+    #[arg(long, default_value = "Elgato Key Light")]
+    pub product_name: String,
+    /// Hardware board type, part of the WiFi encryption key
+    #[arg(long, default_value_t = 53)]
+    pub board_type: u16,
+    /// Firmware build number, part of the WiFi encryption key
+    #[arg(long, default_value_t = 218)]
+    pub firmware_build: u16,
+    /// Firmware version
+    #[arg(long, default_value = "1.0.3")]
+    pub firmware_version: String,
+    /// Serial number
+    #[arg(long, default_value = "BW00000000")]
+    pub serial: String,
+    /// MAC address
+    #[arg(long, default_value = "3C:6A:9D:00:00:00")]
+    pub mac: String,
+    /// Display name
+    #[arg(long, default_value = "")]
+    pub display_name: String,
+    /// Number of lights on the device
+    #[arg(long, default_value_t = 1)]
+    pub lights: u8,
+    /// Report a battery, like the Key Light Mini
+    #[arg(long)]
+    pub battery: bool,
+    /// Inject a fault as <path>:<trigger>:<fault>, for example lights:nth=3:drop [repeatable]
+    ///
+    /// path: * or a path below /elgato, such as lights/settings
+    /// trigger: always, nth=N, first=N, from=N or every=N
+    /// fault: latency=MS, drop, status=CODE[,ERROR_CODE], malformed or reboot
+    #[arg(long, verbatim_doc_comment)]
+    pub fault: Vec<FaultRule>,
+}
@@ -0,0 +1,376 @@
+use crate::{
+    http::{Request, Response},
+    wifi::decrypt_wifi_payload,
+};
+use open_ecc::{
+    contracts::{
+        AccessoryInfoGet, AccessoryInfoPut, AdjustBrightness, AutoMode, BatteryInfo,
+        BatterySettingsGet, BatterySettingsPut, BatteryStatus, EnergySaving, Favourite, JsonError,
+        JsonErrors, LightGet, LightsGet, LightsPut, LightsSettingsGet, LightsSettingsPut,
+        PowerOnBehavior, PowerSource, RemoteControl, Scene, WifiConfig, WifiInfo,
+    },
+    units::{ApiTemperature, Brightness, Kelvin},
+};
+use serde::{Serialize, de::DeserializeOwned};
+
+pub(crate) const NAMESPACE: &str = "/elgato";
+
+/// Error code for a body that could not be parsed
+pub const ERROR_INVALID_BODY: i32 = 1;
+/// Error code for a value outside of its range
+pub const ERROR_INVALID_VALUE: i32 = 2;
+/// Error code for an unknown path or method
+pub const ERROR_NOT_FOUND: i32 = 3;
+/// Error code for a status injected by a fault
+pub const ERROR_INJECTED: i32 = 4;
+
+#[derive(Debug, Clone)]
+pub struct DeviceConfig {
+    pub product_name: String,
+    pub hardware_board_type: u16,
+    pub hardware_revision: String,
+    pub firmware_build_number: u16,
+    pub firmware_version: String,
+    pub serial_number: String,
+    pub mac_address: String,
+    /// The user specified name of the device
+    pub display_name: String,
+    pub number_of_lights: u8,
+    /// Whether the device has a battery, like the Key Light Mini
+    pub battery: bool,
+}
+
+impl Default for DeviceConfig {
+    fn default() -> Self {
+        Self {
+            product_name: "Elgato Key Light".to_string(),
+            hardware_board_type: 53,
+            hardware_revision: "1.0".to_string(),
+            firmware_build_number: 218,
+            firmware_version: "1.0.3".to_string(),
+            serial_number: "BW00000000".to_string(),
+            mac_address: "3C:6A:9D:00:00:00".to_string(),
+            display_name: String::new(),
+            number_of_lights: 1,
+            battery: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeviceState {
+    pub accessory_info: AccessoryInfoGet,
+    pub lights: LightsGet,
+    pub settings: LightsSettingsGet,
+    /// Last WiFi configuration received through `/wifi-info`
+    pub wifi_config: Option<WifiConfig>,
+    /// Number of `/identify` requests received
+    pub identify_count: u32,
+    /// Scene running on each light, if any
+    pub scenes: Vec<Option<Scene>>,
+    /// Only present on devices with a battery
+    pub battery_info: Option<BatteryInfo>,
+    /// Only present on devices with a battery
+    pub battery_settings: Option<BatterySettingsGet>,
+}
+
+impl DeviceState {
+    // Constructors
+
+    pub fn new(config: &DeviceConfig) -> Self {
+        let accessory_info = AccessoryInfoGet {
+            product_name: config.product_name.clone(),
+            hardware_board_type: config.hardware_board_type,
+            hardware_revision: config.hardware_revision.clone(),
+            mac_address: config.mac_address.clone(),
+            firmware_build_number: config.firmware_build_number,
+            firmware_version: config.firmware_version.clone(),
+            serial_number: config.serial_number.clone(),
+            display_name: config.display_name.clone(),
+            features: match config.battery {
+                true => vec!["lights".to_string(), "battery".to_string()],
+                false => vec!["lights".to_string()],
+            },
+            wifi_info: WifiInfo {
+                ssid: "Simulator".to_string(),
+                frequency_mhz: 2400,
+                rssi: -40,
+            },
+            ..Default::default()
+        };
+        let settings = LightsSettingsGet {
+            power_on_behavior: PowerOnBehavior::On,
+            power_on_brightness: Brightness::clamped(20),
+            power_on_temperature: ApiTemperature::from(Kelvin::clamped(4950)),
+            switch_on_duration_ms: 100,
+            switch_off_duration_ms: 300,
+            color_change_duration_ms: 100,
+            remote_control: RemoteControl {
+                favourites: Vec::new(),
+                auto_mode: AutoMode::default(),
+            },
+        };
+        let light = LightGet {
+            on: false,
+            brightness: settings.power_on_brightness,
+            temperature: Some(settings.power_on_temperature),
+            ..Default::default()
+        };
+        let lights = LightsGet {
+            number_of_lights: config.number_of_lights,
+            lights: vec![light; config.number_of_lights as usize],
+        };
+        Self {
+            accessory_info,
+            lights,
+            settings,
+            wifi_config: None,
+            identify_count: 0,
+            scenes: vec![None; config.number_of_lights as usize],
+            battery_info: config.battery.then(|| BatteryInfo {
+                power_source: PowerSource::Battery,
+                level: 80.0,
+                status: BatteryStatus::Draining,
+                current_battery_voltage: 7800,
+                ..Default::default()
+            }),
+            battery_settings: config.battery.then_some(BatterySettingsGet {
+                energy_saving: EnergySaving {
+                    enable: false,
+                    minimum_battery_level: 15.0,
+                    disable_wifi: false,
+                    adjust_brightness: AdjustBrightness {
+                        enable: false,
+                        brightness: 10.0,
+                    },
+                },
+                bypass: false,
+            }),
+        }
+    }
+
+    // Public
+
+    /// Restart the device, resetting every light to the power-on values in the settings
+    pub fn reboot(&mut self) {
+        let light = LightGet {
+            on: self.settings.power_on_behavior != PowerOnBehavior::Off,
+            brightness: self.settings.power_on_brightness,
+            temperature: Some(self.settings.power_on_temperature),
+            ..Default::default()
+        };
+        self.lights.lights.fill(light);
+    }
+
+    pub(crate) fn handle(&mut self, request: &Request) -> Response {
+        let Some(path) = request.path.strip_prefix(NAMESPACE) else {
+            return not_found();
+        };
+        match (request.method.as_str(), path) {
+            ("GET", "/lights") => ok(&self.lights),
+            ("PUT", "/lights") => self.lights_put(&request.body),
+            ("GET", "/lights/settings") => ok(&self.settings),
+            ("PUT", "/lights/settings") => self.lights_settings_put(&request.body),
+            ("GET", "/accessory-info") => ok(&self.accessory_info),
+            ("PUT", "/accessory-info") => self.accessory_info_put(&request.body),
+            ("POST", "/identify") => {
+                self.identify_count += 1;
+                Response::empty(200)
+            }
+            ("PUT", "/wifi-info") => self.wifi_info_put(&request.body),
+            ("GET", "/battery-info") => match &self.battery_info {
+                Some(x) => ok(x),
+                None => not_found(),
+            },
+            ("GET", "/battery-settings") => match &self.battery_settings {
+                Some(x) => ok(x),
+                None => not_found(),
+            },
+            ("PUT", "/battery-settings") => self.battery_settings_put(&request.body),
+            _ => not_found(),
+        }
+    }
+
+    // Private
+
+    fn lights_put(&mut self, body: &[u8]) -> Response {
+        let payload = match parse::<LightsPut>(body) {
+            Ok(x) => x,
+            Err(response) => return response,
+        };
+        if let Some(temperature) = payload
+            .lights
+            .iter()
+            .filter_map(|x| x.temperature)
+            .find(|x| !x.is_valid())
+        {
+            return error(
+                400,
+                ERROR_INVALID_VALUE,
+                &format!(
+                    "Temperature {} is outside of {} - {}",
+                    temperature,
+                    ApiTemperature::MIN,
+                    ApiTemperature::MAX
+                ),
+            );
+        }
+        for ((light, scene), put) in self
+            .lights
+            .lights
+            .iter_mut()
+            .zip(self.scenes.iter_mut())
+            .zip(payload.lights)
+        {
+            if put.scene.is_some() {
+                *scene = put.scene;
+            } else if put.temperature.is_some() || put.hue.is_some() || put.saturation.is_some() {
+                *scene = None;
+            }
+            if let Some(on) = put.on {
+                light.on = on;
+            }
+            if let Some(brightness) = put.brightness {
+                light.brightness = brightness;
+            }
+            if let Some(temperature) = put.temperature {
+                light.temperature = Some(temperature);
+                light.hue = None;
+                light.saturation = None;
+            }
+            if put.hue.is_some() || put.saturation.is_some() {
+                light.temperature = None;
+                light.hue = put.hue.or(light.hue).or(Some(0.0));
+                light.saturation = put.saturation.or(light.saturation).or(Some(0.0));
+            }
+        }
+        ok(&self.lights)
+    }
+
+    fn lights_settings_put(&mut self, body: &[u8]) -> Response {
+        let payload = match parse::<LightsSettingsPut>(body) {
+            Ok(x) => x,
+            Err(response) => return response,
+        };
+        let settings = &mut self.settings;
+        if let Some(x) = payload.power_on_behavior {
+            settings.power_on_behavior = x;
+        }
+        if let Some(x) = payload.power_on_brightness {
+            settings.power_on_brightness = x;
+        }
+        if let Some(x) = payload.power_on_temperature {
+            settings.power_on_temperature = x;
+        }
+        if let Some(x) = payload.switch_on_duration_ms {
+            settings.switch_on_duration_ms = x;
+        }
+        if let Some(x) = payload.switch_off_duration_ms {
+            settings.switch_off_duration_ms = x;
+        }
+        if let Some(x) = payload.color_change_duration_ms {
+            settings.color_change_duration_ms = x;
+        }
+        if let Some(remote_control) = payload.remote_control {
+            if let Some(favourites) = remote_control.favourites {
+                settings.remote_control.favourites = favourites
+                    .into_iter()
+                    .map(|x| Favourite {
+                        brightness: x.brightness.unwrap_or_default(),
+                        temperature: x.temperature.unwrap_or(settings.power_on_temperature),
+                    })
+                    .collect();
+            }
+            if let Some(target_lux_value) =
+                remote_control.auto_mode.and_then(|x| x.target_lux_value)
+            {
+                settings.remote_control.auto_mode.target_lux_value = target_lux_value;
+            }
+        }
+        ok(&self.settings)
+    }
+
+    fn battery_settings_put(&mut self, body: &[u8]) -> Response {
+        let Some(settings) = &mut self.battery_settings else {
+            return not_found();
+        };
+        let payload = match parse::<BatterySettingsPut>(body) {
+            Ok(x) => x,
+            Err(response) => return response,
+        };
+        if let Some(x) = payload.bypass {
+            settings.bypass = x;
+        }
+        if let Some(put) = payload.energy_saving {
+            let energy_saving = &mut settings.energy_saving;
+            if let Some(x) = put.enable {
+                energy_saving.enable = x;
+            }
+            if let Some(x) = put.minimum_battery_level {
+                energy_saving.minimum_battery_level = x;
+            }
+            if let Some(x) = put.disable_wifi {
+                energy_saving.disable_wifi = x;
+            }
+            if let Some(put) = put.adjust_brightness {
+                if let Some(x) = put.enable {
+                    energy_saving.adjust_brightness.enable = x;
+                }
+                if let Some(x) = put.brightness {
+                    energy_saving.adjust_brightness.brightness = x;
+                }
+            }
+        }
+        ok(settings)
+    }
+
+    fn accessory_info_put(&mut self, body: &[u8]) -> Response {
+        let payload = match parse::<AccessoryInfoPut>(body) {
+            Ok(x) => x,
+            Err(response) => return response,
+        };
+        if let Some(display_name) = payload.display_name {
+            self.accessory_info.display_name = display_name;
+        }
+        ok(&self.accessory_info)
+    }
+
+    fn wifi_info_put(&mut self, body: &[u8]) -> Response {
+        match decrypt_wifi_payload(&self.accessory_info, body) {
+            Ok(wifi_config) => {
+                self.wifi_config = Some(wifi_config);
+                Response::empty(200)
+            }
+            Err(e) => error(400, ERROR_INVALID_BODY, &e),
+        }
+    }
+}
+
+pub(crate) fn error(status: u16, code: i32, message: &str) -> Response {
+    let errors = JsonErrors {
+        errors: vec![JsonError {
+            message: message.to_string(),
+            code,
+        }],
+    };
+    json(status, &errors)
+}
+
+fn not_found() -> Response {
+    error(404, ERROR_NOT_FOUND, "Not found")
+}
+
+fn ok<T: Serialize>(value: &T) -> Response {
+    json(200, value)
+}
+
+fn json<T: Serialize>(status: u16, value: &T) -> Response {
+    Response::json(
+        status,
+        serde_json::to_vec(value).expect("Failed to serialize JSON"),
+    )
+}
+
+fn parse<T: DeserializeOwned>(body: &[u8]) -> Result<T, Response> {
+    serde_json::from_slice(body).map_err(|e| error(400, ERROR_INVALID_BODY, &e.to_string()))
+}
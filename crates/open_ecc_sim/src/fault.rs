@@ -0,0 +1,246 @@
+use crate::device::{ERROR_INJECTED, NAMESPACE};
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Delay the response
+    Latency(Duration),
+    /// Close the connection without responding
+    Drop,
+    /// Respond with an error status and a `JsonErrors` body
+    Status {
+        status: u16,
+        code: i32,
+        message: String,
+    },
+    /// Respond with a body that isn't valid JSON
+    MalformedJson,
+    /// Reset the lights to their power-on values before handling the request
+    Reboot,
+}
+
+/// Which requests a fault applies to, counted from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    Always,
+    /// Only the nth request
+    Nth(u32),
+    /// The first n requests
+    First(u32),
+    /// The nth request and every one after it
+    From(u32),
+    /// Every nth request
+    Every(u32),
+}
+
+impl Trigger {
+    fn matches(&self, count: u32) -> bool {
+        match *self {
+            Self::Always => true,
+            Self::Nth(n) => count == n,
+            Self::First(n) => count <= n,
+            Self::From(n) => count >= n,
+            Self::Every(n) => n != 0 && count.is_multiple_of(n),
+        }
+    }
+}
+
+/// A fault injected into requests for a path, or any path when `path` is `None`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaultRule {
+    /// Path below the `/elgato` namespace, for example `/lights/settings`
+    pub path: Option<String>,
+    pub trigger: Trigger,
+    pub fault: Fault,
+}
+
+impl FaultRule {
+    pub fn new(path: Option<&str>, trigger: Trigger, fault: Fault) -> Self {
+        Self {
+            path: path.map(normalize_path),
+            trigger,
+            fault,
+        }
+    }
+}
+
+/// Parses `<path>:<trigger>:<fault>`, for example `lights:nth=3:drop` or `*:every=2:latency=500`.
+///
+/// - path: `*` for any, or a path such as `lights/settings`
+/// - trigger: `always`, `nth=N`, `first=N`, `from=N` or `every=N`
+/// - fault: `latency=MS`, `drop`, `status=CODE[,ERROR_CODE]`, `malformed` or `reboot`
+impl FromStr for FaultRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, ':');
+        let (Some(path), Some(trigger), Some(fault)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!("Expected <path>:<trigger>:<fault>, got '{}'", s));
+        };
+        let path = match path {
+            "*" => None,
+            x => Some(x),
+        };
+        Ok(Self::new(path, trigger.parse()?, fault.parse()?))
+    }
+}
+
+impl FromStr for Trigger {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "always" {
+            return Ok(Self::Always);
+        }
+        let (name, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Unknown trigger '{}'", s))?;
+        let n = value
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid request count '{}'", value))?;
+        match name {
+            "nth" => Ok(Self::Nth(n)),
+            "first" => Ok(Self::First(n)),
+            "from" => Ok(Self::From(n)),
+            "every" => Ok(Self::Every(n)),
+            _ => Err(format!("Unknown trigger '{}'", name)),
+        }
+    }
+}
+
+impl FromStr for Fault {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = s.split_once('=').unwrap_or((s, ""));
+        match name {
+            "drop" => Ok(Self::Drop),
+            "malformed" => Ok(Self::MalformedJson),
+            "reboot" => Ok(Self::Reboot),
+            "latency" => value
+                .parse::<u64>()
+                .map(|x| Self::Latency(Duration::from_millis(x)))
+                .map_err(|_| format!("Invalid latency '{}', expected milliseconds", value)),
+            "status" => {
+                let (status, code) = value.split_once(',').unwrap_or((value, ""));
+                let status = status
+                    .parse::<u16>()
+                    .ok()
+                    .filter(|x| (400..600).contains(x))
+                    .ok_or_else(|| format!("Invalid status '{}', expected 400 - 599", status))?;
+                let code = match code {
+                    "" => ERROR_INJECTED,
+                    x => x
+                        .parse::<i32>()
+                        .map_err(|_| format!("Invalid error code '{}'", x))?,
+                };
+                Ok(Self::Status {
+                    status,
+                    code,
+                    message: "Injected fault".to_string(),
+                })
+            }
+            _ => Err(format!("Unknown fault '{}'", name)),
+        }
+    }
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Latency(x) => write!(f, "latency of {}ms", x.as_millis()),
+            Self::Drop => write!(f, "dropped connection"),
+            Self::Status { status, code, .. } => write!(f, "status {} (code {})", status, code),
+            Self::MalformedJson => write!(f, "malformed JSON"),
+            Self::Reboot => write!(f, "reboot"),
+        }
+    }
+}
+
+/// Rules along with the request counts they are evaluated against
+#[derive(Debug, Default)]
+pub(crate) struct FaultPlan {
+    rules: Vec<FaultRule>,
+    counts: HashMap<String, u32>,
+    total: u32,
+}
+
+impl FaultPlan {
+    pub fn add(&mut self, rule: FaultRule) {
+        self.rules.push(rule);
+    }
+
+    pub fn clear(&mut self) {
+        self.rules.clear();
+        self.counts.clear();
+        self.total = 0;
+    }
+
+    /// Count the request and return the faults that apply to it
+    pub fn next(&mut self, path: &str) -> Vec<Fault> {
+        let path = normalize_path(path);
+        self.total += 1;
+        let count = self.counts.entry(path.clone()).or_default();
+        *count += 1;
+        let count = *count;
+
+        self.rules
+            .iter()
+            .filter(|rule| match &rule.path {
+                Some(x) => *x == path && rule.trigger.matches(count),
+                None => rule.trigger.matches(self.total),
+            })
+            .map(|rule| rule.fault.clone())
+            .collect()
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    let path = path.strip_prefix(NAMESPACE).unwrap_or(path);
+    format!("/{}", path.trim_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rule() {
+        let rule = "lights/settings:nth=3:drop".parse::<FaultRule>().unwrap();
+        assert_eq!(
+            rule,
+            FaultRule::new(Some("/lights/settings"), Trigger::Nth(3), Fault::Drop)
+        );
+
+        let rule = "*:every=2:latency=500".parse::<FaultRule>().unwrap();
+        assert_eq!(rule.path, None);
+        assert_eq!(rule.fault, Fault::Latency(Duration::from_millis(500)));
+
+        let rule = "lights:always:status=503,7".parse::<FaultRule>().unwrap();
+        assert!(matches!(
+            rule.fault,
+            Fault::Status {
+                status: 503,
+                code: 7,
+                ..
+            }
+        ));
+
+        assert!("lights:drop".parse::<FaultRule>().is_err());
+        assert!("lights:always:status=200".parse::<FaultRule>().is_err());
+        assert!("lights:sometimes:drop".parse::<FaultRule>().is_err());
+    }
+
+    #[test]
+    fn test_plan_counts_per_path() {
+        let mut plan = FaultPlan::default();
+        plan.add(FaultRule::new(Some("lights"), Trigger::Nth(2), Fault::Drop));
+        plan.add(FaultRule::new(None, Trigger::From(4), Fault::MalformedJson));
+
+        assert!(plan.next("/elgato/lights").is_empty());
+        assert!(plan.next("/elgato/accessory-info").is_empty());
+        assert_eq!(plan.next("/elgato/lights"), vec![Fault::Drop]);
+        assert_eq!(plan.next("/elgato/lights"), vec![Fault::MalformedJson]);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod device;
+pub mod fault;
+pub(crate) mod http;
+pub mod server;
+pub(crate) mod wifi;
+
+pub use device::{DeviceConfig, DeviceState};
+pub use fault::{Fault, FaultRule, Trigger};
+pub use server::Simulator;
@@ -0,0 +1,32 @@
+use crate::args::Args;
+use anyhow::Result;
+use clap::Parser;
+use open_ecc_sim::{DeviceConfig, Simulator};
+
+mod args;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let config = DeviceConfig {
+        product_name: args.product_name,
+        hardware_board_type: args.board_type,
+        firmware_build_number: args.firmware_build,
+        firmware_version: args.firmware_version,
+        serial_number: args.serial,
+        mac_address: args.mac,
+        display_name: args.display_name,
+        number_of_lights: args.lights,
+        battery: args.battery,
+        ..Default::default()
+    };
+
+    let simulator = Simulator::bind((args.bind, args.port), config).await?;
+    for rule in args.fault {
+        simulator.add_fault(rule);
+    }
+    println!("Listening on {}", simulator.local_addr());
+
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
@@ -0,0 +1,342 @@
+use crate::{
+    device::{DeviceConfig, DeviceState, error},
+    fault::{Fault, FaultPlan, FaultRule},
+    http::{Response, read_request, write_response},
+};
+use std::{
+    io,
+    net::SocketAddr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::Notify,
+    task::JoinHandle,
+};
+
+/// Time to wait before accepting again after a failure
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A simulated device serving the Elgato API until dropped
+pub struct Simulator {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+struct Shared {
+    state: Mutex<DeviceState>,
+    faults: Mutex<FaultPlan>,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+    /// Notified after each request and change of the state
+    changed: Notify,
+}
+
+/// Counts a request as being handled until dropped
+struct InFlight<'a>(&'a Shared);
+
+impl<'a> InFlight<'a> {
+    // Constructors
+
+    fn new(shared: &'a Shared) -> Self {
+        let count = shared.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        shared.max_in_flight.fetch_max(count, Ordering::SeqCst);
+        Self(shared)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Simulator {
+    // Constructors
+
+    /// Serve on a random port on the loopback interface
+    pub async fn start(config: DeviceConfig) -> io::Result<Self> {
+        Self::bind("127.0.0.1:0", config).await
+    }
+
+    pub async fn bind(addr: impl ToSocketAddrs, config: DeviceConfig) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            state: Mutex::new(DeviceState::new(&config)),
+            faults: Mutex::new(FaultPlan::default()),
+            in_flight: AtomicUsize::new(0),
+            max_in_flight: AtomicUsize::new(0),
+            changed: Notify::new(),
+        });
+        let task = tokio::spawn(serve(listener, shared.clone()));
+        Ok(Self { addr, shared, task })
+    }
+
+    // Public
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Endpoint usable with `Ecc` and `Light`
+    pub fn endpoint(&self) -> String {
+        self.addr.to_string()
+    }
+
+    /// Snapshot of the current device state
+    pub fn state(&self) -> DeviceState {
+        self.shared.state.lock().unwrap().clone()
+    }
+
+    /// Modify the device state, as if changed from the hardware buttons
+    pub fn update_state<F>(&self, f: F)
+    where
+        F: FnOnce(&mut DeviceState),
+    {
+        f(&mut self.shared.state.lock().unwrap());
+        self.shared.changed.notify_waiters();
+    }
+
+    /// Wait until the device state satisfies `f`, checked after each request
+    pub async fn wait_until<F>(&self, f: F)
+    where
+        F: Fn(&DeviceState) -> bool,
+    {
+        loop {
+            let changed = self.shared.changed.notified();
+            if f(&self.shared.state.lock().unwrap()) {
+                return;
+            }
+            changed.await;
+        }
+    }
+
+    /// Inject a fault into subsequent requests
+    pub fn add_fault(&self, rule: FaultRule) {
+        self.shared.faults.lock().unwrap().add(rule);
+    }
+
+    /// Most requests handled at the same time so far
+    pub fn max_in_flight(&self) -> usize {
+        self.shared.max_in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Remove all faults and reset the request counts
+    pub fn clear_faults(&self) {
+        self.shared.faults.lock().unwrap().clear();
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn serve(listener: TcpListener, shared: Arc<Shared>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(handle_connection(stream, shared.clone()));
+            }
+            // Errors such as running out of file descriptors last a while
+            Err(e) => {
+                eprintln!("Failed to accept a connection: {}", e);
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+            }
+        }
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, shared: Arc<Shared>) {
+    let Ok(Some(request)) = read_request(&mut stream).await else {
+        return;
+    };
+    let _in_flight = InFlight::new(&shared);
+    let faults = shared.faults.lock().unwrap().next(&request.path);
+
+    if faults.contains(&Fault::Reboot) {
+        shared.state.lock().unwrap().reboot();
+    }
+    for fault in &faults {
+        if let Fault::Latency(duration) = fault {
+            tokio::time::sleep(*duration).await;
+        }
+    }
+
+    let mut response = None;
+    for fault in &faults {
+        match fault {
+            Fault::Drop => return,
+            Fault::Status {
+                status,
+                code,
+                message,
+            } => response = Some(error(*status, *code, message)),
+            Fault::MalformedJson => {
+                response = Some(Response::json(200, b"{\"lights\":[{\"on\":".to_vec()))
+            }
+            _ => continue,
+        }
+        break;
+    }
+
+    let response = response.unwrap_or_else(|| shared.state.lock().unwrap().handle(&request));
+    shared.changed.notify_waiters();
+    _ = write_response(&mut stream, &response).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fault::Trigger;
+    use open_ecc::{
+        Error,
+        contracts::{AccessoryInfoPut, LightPut, LightsPut, WifiConfig, WifiSecurity},
+        ecc::Ecc,
+        light::Light,
+        units::{ApiTemperature, Brightness},
+    };
+
+    #[tokio::test]
+    async fn test_accessory_info_and_identify() {
+        let simulator = Simulator::start(DeviceConfig::default()).await.unwrap();
+        let ecc = Ecc::default();
+        let endpoint = simulator.endpoint();
+
+        let payload = AccessoryInfoPut {
+            display_name: Some("Desk".to_string()),
+        };
+        ecc.accessory_info_put(&endpoint, &payload).await.unwrap();
+        ecc.identify(&endpoint).await.unwrap();
+
+        let info = ecc.accessory_info_get(&endpoint).await.unwrap();
+        assert_eq!(info.display_name, "Desk");
+        assert_eq!(info.product_name, "Elgato Key Light");
+        assert_eq!(simulator.state().identify_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_wifi_config() {
+        let simulator = Simulator::start(DeviceConfig::default()).await.unwrap();
+        let ecc = Ecc::default();
+
+        let payload = WifiConfig {
+            ssid: "Studio".to_string(),
+            passphrase: Some("hunter22".to_string()),
+            security_type: WifiSecurity::WpaOrWpa2Personal,
+            channel: None,
+        };
+        ecc.wifi_config(&simulator.endpoint(), &payload)
+            .await
+            .unwrap();
+
+        let wifi_config = simulator.state().wifi_config.unwrap();
+        assert_eq!(wifi_config.ssid, "Studio");
+        assert_eq!(wifi_config.passphrase.as_deref(), Some("hunter22"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_value() {
+        let simulator = Simulator::start(DeviceConfig::default()).await.unwrap();
+        let ecc = Ecc::default();
+
+        let payload = LightsPut {
+            lights: vec![LightPut {
+                temperature: serde_json::from_str::<ApiTemperature>("400").ok(),
+                ..Default::default()
+            }],
+        };
+        let error = ecc
+            .lights_put(&simulator.endpoint(), &payload)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Status { .. }));
+        assert_eq!(error.codes(), vec![crate::device::ERROR_INVALID_VALUE]);
+    }
+
+    #[tokio::test]
+    async fn test_fault_latency() {
+        let simulator = Simulator::start(DeviceConfig::default()).await.unwrap();
+        simulator.add_fault(FaultRule::new(
+            Some("lights"),
+            Trigger::Always,
+            Fault::Latency(Duration::from_millis(500)),
+        ));
+        let ecc = Ecc::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        let error = ecc.lights_get(&simulator.endpoint()).await.unwrap_err();
+        assert!(matches!(error, Error::Timeout(_)));
+        ecc.accessory_info_get(&simulator.endpoint()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fault_drop_on_nth_request() {
+        let simulator = Simulator::start(DeviceConfig::default()).await.unwrap();
+        simulator.add_fault(FaultRule::new(None, Trigger::Nth(2), Fault::Drop));
+        let ecc = Ecc::default();
+        let endpoint = simulator.endpoint();
+
+        ecc.lights_get(&endpoint).await.unwrap();
+        let error = ecc.lights_get(&endpoint).await.unwrap_err();
+        assert!(matches!(error, Error::Transport(_)));
+        assert!(error.is_offline());
+        ecc.lights_get(&endpoint).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fault_status_and_malformed_json() {
+        let simulator = Simulator::start(DeviceConfig::default()).await.unwrap();
+        simulator.add_fault("lights:first=1:status=503".parse().unwrap());
+        simulator.add_fault("accessory-info:always:malformed".parse().unwrap());
+        let ecc = Ecc::default();
+        let endpoint = simulator.endpoint();
+
+        let error = ecc.lights_get(&endpoint).await.unwrap_err();
+        assert!(matches!(error, Error::Status { status, .. } if status.as_u16() == 503));
+        assert_eq!(error.codes(), vec![crate::device::ERROR_INJECTED]);
+
+        let error = ecc.accessory_info_get(&endpoint).await.unwrap_err();
+        assert!(matches!(error, Error::Deserialize(_)));
+
+        simulator.clear_faults();
+        ecc.accessory_info_get(&endpoint).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fault_reboot() {
+        let simulator = Simulator::start(DeviceConfig::default()).await.unwrap();
+        let ecc = Ecc::default();
+        let endpoint = simulator.endpoint();
+        let light = Light::new(&ecc, &endpoint);
+
+        simulator.add_fault(FaultRule::new(
+            Some("lights"),
+            Trigger::Nth(3),
+            Fault::Reboot,
+        ));
+        light
+            .brightness_set(Brightness::new(80).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(simulator.state().lights.lights[0].brightness.get(), 80);
+
+        let lights = ecc.lights_get(&endpoint).await.unwrap();
+        let settings = simulator.state().settings;
+        assert!(lights.lights[0].on);
+        assert_eq!(lights.lights[0].brightness, settings.power_on_brightness);
+        assert_eq!(
+            lights.lights[0].temperature,
+            Some(settings.power_on_temperature)
+        );
+    }
+}
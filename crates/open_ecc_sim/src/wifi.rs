@@ -0,0 +1,72 @@
+use cipher::{BlockDecryptMut, KeyIvInit, block_padding::NoPadding};
+use open_ecc::contracts::{AccessoryInfoGet, WifiConfig};
+
+type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+
+const WIFI_IV: &str = "049F6F1149C6F84B1B14913C71E9CDBE";
+
+/// Key the device derives from its board type and firmware build
+fn get_encryption_key(data: &AccessoryInfoGet) -> String {
+    format!(
+        "4CB4{:04X}B0EADDEEEB2A038A31{:04X}56",
+        data.hardware_board_type.swap_bytes(),
+        data.firmware_build_number.swap_bytes()
+    )
+}
+
+/// Read a WiFi configuration encrypted by `Ecc::wifi_config`, as performed by the device
+pub(crate) fn decrypt_wifi_payload(
+    accessory_info: &AccessoryInfoGet,
+    bytes: &[u8],
+) -> Result<WifiConfig, String> {
+    if bytes.len() < 32 || !bytes.len().is_multiple_of(16) {
+        return Err(format!(
+            "Encrypted payload length {} is not a multiple of 16",
+            bytes.len()
+        ));
+    }
+
+    let iv_bytes = hex::decode(WIFI_IV).expect("Invalid IV hex");
+
+    let key = get_encryption_key(accessory_info);
+
+    let key_bytes = hex::decode(key).expect("Invalid key hex string");
+
+    let aes_cbc =
+        Aes128CbcDec::new_from_slices(&key_bytes, &iv_bytes).expect("Invalid key/iv length");
+
+    let mut data = bytes.to_vec();
+    let decrypted_bytes = aes_cbc
+        .decrypt_padded_mut::<NoPadding>(&mut data)
+        .expect("Decryption failure");
+
+    // The first block is random
+    let json = &decrypted_bytes[16..];
+    let end = json.iter().rposition(|x| *x != 0).map_or(0, |x| x + 1);
+    serde_json::from_slice(&json[..end]).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_encryption_key() {
+        let accessory_info = AccessoryInfoGet {
+            firmware_build_number: 198,
+            hardware_board_type: 205,
+            ..Default::default()
+        };
+        assert_eq!(
+            get_encryption_key(&accessory_info),
+            "4CB4CD00B0EADDEEEB2A038A31C60056"
+        );
+    }
+
+    #[test]
+    fn test_decrypt_wifi_payload_length() {
+        let accessory_info = AccessoryInfoGet::default();
+        assert!(decrypt_wifi_payload(&accessory_info, &[0; 16]).is_err());
+        assert!(decrypt_wifi_payload(&accessory_info, &[0; 40]).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+// Each test binary only uses some of the helpers
+#![allow(dead_code)]
+
+use open_ecc::{
+    ecc::Ecc,
+    light::Light,
+    units::{Brightness, Kelvin},
+};
+use open_ecc_sim::{DeviceConfig, DeviceState, Simulator};
+use tokio::net::TcpListener;
+
+/// A simulated device and a client talking to it
+pub struct Device {
+    pub simulator: Simulator,
+    pub ecc: Ecc,
+    pub endpoint: String,
+}
+
+impl Device {
+    // Constructors
+
+    pub async fn start(config: DeviceConfig) -> Self {
+        let simulator = Simulator::start(config).await.unwrap();
+        let endpoint = simulator.endpoint();
+        Self {
+            simulator,
+            ecc: Ecc::default(),
+            endpoint,
+        }
+    }
+
+    // Public
+
+    /// Every light of the device
+    pub fn light(&self) -> Light<'_> {
+        Light::new(&self.ecc, &self.endpoint)
+    }
+
+    pub fn state(&self) -> DeviceState {
+        self.simulator.state()
+    }
+}
+
+pub fn brightness(value: u8) -> Brightness {
+    Brightness::new(value).unwrap()
+}
+
+pub fn kelvin(value: u16) -> Kelvin {
+    Kelvin::new(value).unwrap()
+}
+
+pub fn light_strip() -> DeviceConfig {
+    DeviceConfig {
+        product_name: "Elgato Light Strip".to_string(),
+        hardware_board_type: 70,
+        ..Default::default()
+    }
+}
+
+/// A device with `number_of_lights` lights
+pub fn lights(number_of_lights: u8) -> DeviceConfig {
+    DeviceConfig {
+        number_of_lights,
+        ..Default::default()
+    }
+}
+
+/// An endpoint nothing answers on
+pub async fn offline_endpoint() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap().to_string()
+}
@@ -0,0 +1,213 @@
+use common::{Device, brightness, kelvin, light_strip, lights, offline_endpoint};
+use open_ecc::{
+    Error,
+    backup::Backup,
+    color::Hsv,
+    identity::Identity,
+    light::{Light, LightGroup},
+    snapshot::Snapshot,
+};
+use open_ecc_sim::{DeviceConfig, Fault, FaultRule, Trigger};
+use std::time::Duration;
+
+mod common;
+
+#[tokio::test]
+async fn test_group() {
+    let device = Device::start(lights(4)).await;
+    // Requests are held long enough for every member to have sent its own
+    device.simulator.add_fault(FaultRule::new(
+        None,
+        Trigger::Always,
+        Fault::Latency(Duration::from_millis(300)),
+    ));
+    let offline_endpoint = offline_endpoint().await;
+    let light = device.light();
+    let members = (0..4).map(|i| light.channel(i));
+    let offline = Light::new(&device.ecc, &offline_endpoint);
+    let group = LightGroup::new(members.chain([offline]));
+
+    let report = group.on().await;
+    // Members are talked to at the same time rather than one after the other
+    assert_eq!(device.simulator.max_in_flight(), 4);
+    assert!(!report.is_success());
+    assert_eq!(report.succeeded().count(), 4);
+    let failed = report.failed().collect::<Vec<_>>();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].light.endpoint(), offline_endpoint);
+    assert!(device.state().lights.lights.iter().all(|x| x.on));
+
+    // Unless limited
+    let device = Device::start(lights(4)).await;
+    let light = device.light();
+    let group = LightGroup::new((0..4).map(|i| light.channel(i))).concurrency(1);
+    assert!(group.on().await.is_success());
+    assert_eq!(device.simulator.max_in_flight(), 1);
+}
+
+#[tokio::test]
+async fn test_load_capabilities() {
+    let strip = Device::start(DeviceConfig {
+        number_of_lights: 2,
+        ..light_strip()
+    })
+    .await;
+    let offline_endpoint = offline_endpoint().await;
+
+    let light = strip.light();
+    let other = Light::new(&strip.ecc, &offline_endpoint);
+    let group = LightGroup::new([light.channel(0), light.channel(1), other]);
+    let (group, failed) = group.load_capabilities().await;
+    assert_eq!(group.lights().len(), 2);
+    let failed = failed.failed().collect::<Vec<_>>();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].light.endpoint(), offline_endpoint);
+
+    // Features are checked without reading the accessory info again
+    strip.simulator.add_fault(FaultRule::new(
+        Some("/accessory-info"),
+        Trigger::Always,
+        Fault::Drop,
+    ));
+    let color = "#ff8800".parse::<Hsv>().unwrap();
+    assert!(group.color_set(color).await.is_success());
+}
+
+#[tokio::test]
+async fn test_verify_identities() {
+    let expected = Device::start(lights(2)).await;
+    let moved = Device::start(DeviceConfig::default()).await;
+    let untracked = Device::start(DeviceConfig::default()).await;
+    let identity = Identity::from(&expected.state().accessory_info);
+    let other = Identity {
+        serial_number: "BW999".to_string(),
+        mac_address: String::new(),
+    };
+
+    let light = expected.light().with_identity(&identity);
+    let group = LightGroup::new([
+        light.channel(0),
+        light.channel(1),
+        moved.light().with_identity(&other),
+        untracked.light(),
+    ]);
+    group.off().await;
+    let (group, report) = group.verify_identities().await;
+    assert_eq!(group.lights().len(), 3);
+    let failed = report.failed().collect::<Vec<_>>();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].light.endpoint(), moved.endpoint);
+    assert!(matches!(failed[0].result, Err(Error::WrongDevice { .. })));
+
+    // Nothing is sent to the device that answered as another
+    assert!(group.on().await.is_success());
+    assert!(expected.state().lights.lights.iter().all(|x| x.on));
+    assert!(untracked.state().lights.lights[0].on);
+    assert!(!moved.state().lights.lights[0].on);
+}
+
+#[tokio::test]
+async fn test_snapshot() {
+    let first = Device::start(DeviceConfig::default()).await;
+    let second = Device::start(lights(2)).await;
+    let group = LightGroup::new([first.light(), second.light()]);
+
+    group.on().await;
+    group.brightness_set(brightness(70)).await;
+    second.light().channel(1).off().await.unwrap();
+    let (snapshot, report) = Snapshot::capture(&group, true).await;
+    assert!(report.is_success());
+    assert_eq!(snapshot.devices.len(), 2);
+    let json = serde_json::to_string(&snapshot).unwrap();
+    assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+
+    group.brightness_set(brightness(10)).await;
+    group.temperature_set(kelvin(6000)).await;
+    group.on().await;
+    first
+        .light()
+        .power_on_brightness_set(brightness(90))
+        .await
+        .unwrap();
+
+    let report = snapshot
+        .apply(&group, Some(Duration::from_millis(100)))
+        .await;
+    assert!(report.is_success());
+    assert_eq!(first.state().lights, snapshot.devices[0].lights);
+    assert_eq!(second.state().lights, snapshot.devices[1].lights);
+    assert!(!second.state().lights.lights[1].on);
+    assert_eq!(first.state().settings.power_on_brightness.get(), 20);
+
+    // Lights missing from the snapshot are reported
+    let other = Device::start(DeviceConfig::default()).await;
+    let group = LightGroup::new([other.light()]);
+    let report = snapshot.apply(&group, None).await;
+    assert!(matches!(
+        report.results[0].result,
+        Err(Error::Validation(_))
+    ));
+
+    // Lights that can't be read are reported and left out
+    let offline_endpoint = offline_endpoint().await;
+    let offline = Light::new(&first.ecc, &offline_endpoint);
+    let group = LightGroup::new([offline, first.light()]);
+    let (snapshot, report) = Snapshot::capture(&group, false).await;
+    assert_eq!(snapshot.devices.len(), 1);
+    assert_eq!(snapshot.devices[0].endpoint, first.endpoint);
+    let failed = report.failed().collect::<Vec<_>>();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].light.endpoint(), offline_endpoint);
+    assert_eq!(report.succeeded().count(), 1);
+}
+
+#[tokio::test]
+async fn test_backup() {
+    let config = |n: u8| DeviceConfig {
+        serial_number: format!("BW00{}", n),
+        mac_address: format!("3C:6A:9D:00:00:0{}", n),
+        ..Default::default()
+    };
+    let first = Device::start(config(1)).await;
+    let second = Device::start(config(2)).await;
+    let group = LightGroup::new([first.light(), second.light()]);
+
+    let light = first.light();
+    light.display_name_set("Desk").await.unwrap();
+    light.brightness_set(brightness(75)).await.unwrap();
+    light.on().await.unwrap();
+    light
+        .switch_on_duration_set(Duration::from_millis(500))
+        .await
+        .unwrap();
+    let report = group.run(|light| async move { light.backup().await }).await;
+    let backup = Backup::new(
+        report
+            .results
+            .into_iter()
+            .map(|x| x.result.unwrap())
+            .collect(),
+    );
+    let json = serde_json::to_string(&backup).unwrap();
+    assert_eq!(serde_json::from_str::<Backup>(&json).unwrap(), backup);
+
+    // The first light comes back factory reset on another address
+    let reset = Device::start(config(1)).await;
+    let group = LightGroup::new([reset.light()]);
+    assert!(backup.restore(&group).await.is_success());
+    let state = reset.state();
+    assert_eq!(state.accessory_info.display_name, "Desk");
+    assert_eq!(state.lights, first.state().lights);
+    assert_eq!(state.settings.switch_on_duration_ms, 500);
+
+    // Its settings can be copied onto the second light
+    let group = LightGroup::new([second.light()]);
+    let source = backup.find_by_id("BW001").unwrap();
+    assert!(source.clone_settings(&group).await.is_success());
+    assert_eq!(second.state().settings.switch_on_duration_ms, 500);
+    assert_eq!(second.state().accessory_info.display_name, "");
+
+    let unknown = Device::start(config(3)).await;
+    let group = LightGroup::new([unknown.light()]);
+    assert!(!backup.restore(&group).await.is_success());
+}
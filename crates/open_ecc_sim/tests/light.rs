@@ -0,0 +1,241 @@
+use common::{Device, brightness, kelvin, light_strip, lights};
+use open_ecc::{
+    Error,
+    adjust::{BrightnessValue, TemperatureValue},
+    capabilities::Capabilities,
+    color::Hsv,
+    contracts::{Scene, SceneFrame, SegmentColor},
+    identity::Identity,
+    units::ApiTemperature,
+};
+use open_ecc_sim::DeviceConfig;
+
+mod common;
+
+#[tokio::test]
+async fn test_light_control() {
+    let device = Device::start(DeviceConfig::default()).await;
+    let light = device.light();
+
+    light.on().await.unwrap();
+    light.brightness_set(brightness(42)).await.unwrap();
+    light.temperature_set(kelvin(3200)).await.unwrap();
+
+    assert!(light.state_get().await.unwrap());
+    assert_eq!(light.brightness_get().await.unwrap().get(), 42);
+    assert_eq!(light.temperature_get().await.unwrap().get(), 3200);
+
+    light.toggle().await.unwrap();
+    assert!(!device.state().lights.lights[0].on);
+
+    // The exact device value is sent as it is
+    light
+        .api_temperature_set(ApiTemperature::new(200).unwrap())
+        .await
+        .unwrap();
+    let temperature = device.state().lights.lights[0].temperature.unwrap();
+    assert_eq!(temperature.get(), 200);
+}
+
+#[tokio::test]
+async fn test_adjust() {
+    let device = Device::start(DeviceConfig::default()).await;
+    let light = device.light();
+    light.brightness_set(brightness(40)).await.unwrap();
+    light.temperature_set(kelvin(4000)).await.unwrap();
+
+    light
+        .brightness_adjust(BrightnessValue::Scale(-25.0))
+        .await
+        .unwrap();
+    light
+        .temperature_adjust("+250mired".parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(light.brightness_get().await.unwrap().get(), 30);
+    assert_eq!(light.temperature_get().await.unwrap().get(), 2900);
+
+    light
+        .brightness_adjust(BrightnessValue::Relative(90))
+        .await
+        .unwrap();
+    light
+        .temperature_adjust(TemperatureValue::Relative(200))
+        .await
+        .unwrap();
+    assert_eq!(light.brightness_get().await.unwrap().get(), 100);
+    assert_eq!(light.temperature_get().await.unwrap().get(), 3100);
+}
+
+#[tokio::test]
+async fn test_color() {
+    let device = Device::start(light_strip()).await;
+    let light = device.light();
+
+    assert!(matches!(
+        light.color_get().await.unwrap_err(),
+        Error::Unsupported(_)
+    ));
+
+    let color = "#ff8800".parse::<Hsv>().unwrap();
+    light.color_set(color).await.unwrap();
+    let result = light.color_get().await.unwrap();
+    assert_eq!(result.hue, color.hue);
+    assert_eq!(result.saturation, color.saturation);
+    assert_eq!(result.value, 100.0);
+    assert!(matches!(
+        light.temperature_get().await.unwrap_err(),
+        Error::Unsupported(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_capabilities() {
+    let device = Device::start(DeviceConfig::default()).await;
+    let light = device.light();
+
+    let capabilities = light.capabilities().await.unwrap();
+    assert!(capabilities.temperature && !capabilities.color && !capabilities.multi_light);
+    let color = "#ff8800".parse::<Hsv>().unwrap();
+    assert!(matches!(
+        light.color_set(color).await.unwrap_err(),
+        Error::Unsupported("colour")
+    ));
+    assert!(device.state().lights.lights[0].hue.is_none());
+
+    // Known capabilities are used without asking the device
+    let light = light.with_capabilities(Capabilities {
+        color: true,
+        ..capabilities
+    });
+    light.color_set(color).await.unwrap();
+    assert!(device.state().lights.lights[0].hue.is_some());
+}
+
+#[tokio::test]
+async fn test_identity() {
+    let device = Device::start(DeviceConfig::default()).await;
+    let info = device.state().accessory_info;
+
+    let light = device.light();
+    let expected = Identity::from(&info);
+    assert!(light.with_identity(&expected).capabilities().await.is_ok());
+
+    // Another device at the address is refused before anything is sent to it
+    let other = Identity {
+        serial_number: "BW999".to_string(),
+        mac_address: String::new(),
+    };
+    let light = light.with_identity(&other);
+    assert!(matches!(
+        light.accessory_info_get().await.unwrap_err(),
+        Error::WrongDevice { .. }
+    ));
+    assert!(matches!(
+        light.temperature_set(kelvin(3200)).await.unwrap_err(),
+        Error::WrongDevice { .. }
+    ));
+    assert_ne!(
+        device.state().lights.lights[0]
+            .temperature
+            .unwrap()
+            .kelvin()
+            .get(),
+        3200
+    );
+}
+
+#[tokio::test]
+async fn test_temperature_range() {
+    let key_light = Device::start(DeviceConfig::default()).await;
+    let strip = Device::start(light_strip()).await;
+
+    let mut values = Vec::new();
+    for device in [&key_light, &strip] {
+        let light = device.light();
+        light.temperature_set(kelvin(6500)).await.unwrap();
+        values.push(device.state().lights.lights[0].temperature.unwrap());
+        assert_eq!(light.temperature_get().await.unwrap().get(), 6500);
+
+        // Relative values stop at the end of the range
+        light
+            .temperature_adjust(TemperatureValue::Relative(-5000))
+            .await
+            .unwrap();
+        assert_eq!(light.temperature_get().await.unwrap().get(), 2900);
+    }
+    // The same temperature is the same API value on every model
+    assert_eq!(values[0], values[1]);
+}
+
+#[tokio::test]
+async fn test_scene() {
+    let device = Device::start(light_strip()).await;
+    let light = device.light();
+
+    let segment = SegmentColor {
+        hue: 30.0,
+        saturation: 100.0,
+        brightness: 80.0,
+    };
+    let scene = Scene {
+        id: "com.example.sunrise".to_string(),
+        name: "Sunrise".to_string(),
+        frames: vec![
+            SceneFrame {
+                segments: vec![segment; 2],
+                duration_ms: 1000,
+                transition_ms: 500,
+            };
+            3
+        ],
+        ..Default::default()
+    };
+    light.scene_set(&scene).await.unwrap();
+
+    let state = device.state();
+    let stored = state.scenes[0].as_ref().unwrap();
+    assert!(state.lights.lights[0].on);
+    assert_eq!(stored.number_of_scene_elements, 3);
+    assert_eq!(stored.frames, scene.frames);
+
+    light.temperature_set(kelvin(4000)).await.unwrap();
+    assert!(device.state().scenes[0].is_none());
+
+    let empty = Scene::default();
+    assert!(matches!(
+        light.scene_set(&empty).await.unwrap_err(),
+        Error::Validation(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_multiple_lights() {
+    let device = Device::start(lights(2)).await;
+
+    let lights = device.ecc.lights_get(&device.endpoint).await.unwrap();
+    assert_eq!(lights.number_of_lights, 2);
+    assert_eq!(lights.lights.len(), 2);
+
+    let light = device.light();
+    light.channel(1).on().await.unwrap();
+    light
+        .channel(1)
+        .brightness_set(brightness(70))
+        .await
+        .unwrap();
+    let lights = device.state().lights.lights;
+    assert!(!lights[0].on);
+    assert!(lights[1].on);
+    assert_eq!(lights[1].brightness.get(), 70);
+    assert_eq!(light.channel(1).brightness_get().await.unwrap().get(), 70);
+
+    light.off().await.unwrap();
+    assert!(device.state().lights.lights.iter().all(|x| !x.on));
+
+    let error = light.channel(2).on().await.unwrap_err();
+    assert!(matches!(
+        error,
+        Error::NoLightAtIndex { index: 2, count: 2 }
+    ));
+}
@@ -0,0 +1,27 @@
+use common::Device;
+use open_ecc::scan::Scanner;
+use open_ecc_sim::DeviceConfig;
+use std::time::Duration;
+
+mod common;
+
+#[tokio::test]
+async fn test_scan() {
+    let device = Device::start(DeviceConfig::default()).await;
+    let scanner = Scanner::new(&device.ecc)
+        .port(device.simulator.local_addr().port())
+        .timeout(Duration::from_millis(200));
+
+    let lights = scanner.scan("127.0.0.0/30".parse().unwrap()).await.unwrap();
+    assert_eq!(lights.len(), 1);
+    assert_eq!(lights[0].endpoint(), device.endpoint);
+    assert_eq!(lights[0].accessory_info.serial_number, "BW00000000");
+
+    // Answers, but not as an Elgato light
+    device.simulator.update_state(|x| {
+        x.accessory_info.product_name = "Printer".to_string();
+        x.accessory_info.hardware_board_type = 0;
+    });
+    let address = device.simulator.local_addr().ip();
+    assert!(scanner.probe(address).await.is_none());
+}
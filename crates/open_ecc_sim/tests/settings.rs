@@ -0,0 +1,135 @@
+use common::{Device, brightness, kelvin};
+use open_ecc::{
+    Error,
+    contracts::{BatteryStatus, Favourite, LightsSettingsPut, PowerOnBehavior, PowerSource},
+};
+use open_ecc_sim::DeviceConfig;
+use std::time::Duration;
+
+mod common;
+
+#[tokio::test]
+async fn test_settings() {
+    let device = Device::start(DeviceConfig::default()).await;
+    let light = device.light();
+
+    light
+        .power_on_behavior_set(PowerOnBehavior::Off)
+        .await
+        .unwrap();
+    light.power_on_brightness_set(brightness(55)).await.unwrap();
+    light.power_on_temperature_set(kelvin(3200)).await.unwrap();
+    light
+        .switch_on_duration_set(Duration::from_millis(250))
+        .await
+        .unwrap();
+    light
+        .color_change_duration_set(Duration::from_millis(50))
+        .await
+        .unwrap();
+    assert!(
+        light
+            .switch_off_duration_set(Duration::from_secs(120))
+            .await
+            .is_err()
+    );
+
+    let settings = light.settings_get().await.unwrap();
+    assert_eq!(settings.power_on_behavior, PowerOnBehavior::Off);
+    assert_eq!(settings.power_on_brightness.get(), 55);
+    assert_eq!(settings.power_on_temperature.kelvin().get(), 3200);
+    assert_eq!(settings.switch_on_duration_ms, 250);
+    assert_eq!(settings.switch_off_duration_ms, 300);
+    assert_eq!(settings.color_change_duration_ms, 50);
+
+    // Fields left as `None` aren't sent at all
+    let payload = LightsSettingsPut {
+        switch_off_duration_ms: Some(500),
+        ..Default::default()
+    };
+    assert_eq!(
+        serde_json::to_string(&payload).unwrap(),
+        r#"{"switchOffDurationMs":500}"#
+    );
+    light.settings_set(&payload).await.unwrap();
+    assert_eq!(device.state().settings.switch_off_duration_ms, 500);
+
+    device.simulator.update_state(|state| state.reboot());
+    let state = light.state_get().await.unwrap();
+    assert!(!state);
+    assert_eq!(light.brightness_get().await.unwrap().get(), 55);
+}
+
+#[tokio::test]
+async fn test_favourites() {
+    let device = Device::start(DeviceConfig::default()).await;
+    let light = device.light();
+    let favourite = |b, k| Favourite {
+        brightness: brightness(b),
+        temperature: kelvin(k).into(),
+    };
+
+    assert!(light.favourites_get().await.unwrap().is_empty());
+    light.favourites_add(favourite(20, 3000)).await.unwrap();
+    light.favourites_add(favourite(50, 4500)).await.unwrap();
+    light.favourites_add(favourite(90, 6500)).await.unwrap();
+    light.favourites_remove(1).await.unwrap();
+    assert!(light.favourites_remove(5).await.is_err());
+
+    let favourites = light.favourites_get().await.unwrap();
+    assert_eq!(favourites, vec![favourite(20, 3000), favourite(90, 6500)]);
+
+    light.auto_mode_target_set(250).await.unwrap();
+    let settings = device.state().settings;
+    assert_eq!(settings.remote_control.auto_mode.target_lux_value, 250);
+    assert_eq!(settings.remote_control.favourites.len(), 2);
+
+    light.favourites_set(&[]).await.unwrap();
+    assert!(light.favourites_get().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_battery() {
+    let device = Device::start(DeviceConfig {
+        battery: true,
+        ..Default::default()
+    })
+    .await;
+    let light = device.light();
+
+    let info = light.battery_info_get().await.unwrap();
+    assert_eq!(info.power_source, PowerSource::Battery);
+    assert_eq!(info.status, BatteryStatus::Draining);
+    assert_eq!(info.level, 80.0);
+
+    light.energy_saving_set(true).await.unwrap();
+    light
+        .brightness_cap_set(Some(brightness(30)))
+        .await
+        .unwrap();
+    let settings = light.battery_settings_get().await.unwrap();
+    assert!(settings.energy_saving.enable);
+    assert!(settings.energy_saving.adjust_brightness.enable);
+    assert_eq!(settings.energy_saving.adjust_brightness.brightness, 30.0);
+
+    light.brightness_cap_set(None).await.unwrap();
+    let settings = device.state().battery_settings.unwrap();
+    assert!(settings.energy_saving.enable);
+    assert!(!settings.energy_saving.adjust_brightness.enable);
+    assert_eq!(settings.energy_saving.adjust_brightness.brightness, 30.0);
+}
+
+#[tokio::test]
+async fn test_battery_unsupported() {
+    let device = Device::start(DeviceConfig::default()).await;
+    let light = device.light();
+
+    assert!(matches!(
+        light.battery_info_get().await,
+        Err(Error::Unsupported("battery"))
+    ));
+    assert!(matches!(
+        light.energy_saving_set(true).await,
+        Err(Error::Unsupported("battery"))
+    ));
+}
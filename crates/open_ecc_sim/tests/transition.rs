@@ -0,0 +1,103 @@
+use common::{Device, brightness, kelvin};
+use futures::StreamExt;
+use open_ecc::{
+    light::LightGroup,
+    transition::{Easing, Transition, TransitionOutcome},
+    watch::LightEvent,
+};
+use open_ecc_sim::{DeviceConfig, Fault, FaultRule, Trigger};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+mod common;
+
+#[tokio::test]
+async fn test_transition() {
+    let device = Device::start(DeviceConfig::default()).await;
+    let light = device.light();
+    light.brightness_set(brightness(10)).await.unwrap();
+
+    let transition = Transition::new(Duration::from_millis(200))
+        .brightness(brightness(60))
+        .temperature(kelvin(3200))
+        .easing(Easing::EaseInOut)
+        .interval(Duration::from_millis(20));
+    let outcome = light
+        .transition(&transition, &CancellationToken::new())
+        .await
+        .unwrap();
+
+    assert_eq!(outcome, TransitionOutcome::Completed);
+    let state = device.state().lights.lights[0];
+    assert_eq!(state.brightness.get(), 60);
+    assert_eq!(state.temperature.map(|x| x.kelvin().get()), Some(3200));
+}
+
+// Steps only wait for the paused clock, which moves when every task is waiting
+#[tokio::test(start_paused = true)]
+async fn test_transition_cancelled() {
+    let device = Device::start(DeviceConfig::default()).await;
+    let group = LightGroup::new([device.light()]);
+    group.brightness_set(brightness(0)).await;
+
+    // A step of 1 every 100ms, cancelled once the second one is on the light
+    let transition = Transition::new(Duration::from_secs(10)).brightness(brightness(100));
+    let cancel = CancellationToken::new();
+    let stop = async {
+        device
+            .simulator
+            .wait_until(|x| x.lights.lights[0].brightness.get() == 2)
+            .await;
+        cancel.cancel();
+    };
+    let (report, _) = tokio::join!(group.transition(&transition, &cancel), stop);
+
+    assert!(report.is_success());
+    assert_eq!(
+        report.results[0].result.as_ref().unwrap(),
+        &TransitionOutcome::Cancelled
+    );
+    assert_eq!(device.state().lights.lights[0].brightness.get(), 2);
+}
+
+#[tokio::test]
+async fn test_watch() {
+    let device = Device::start(DeviceConfig::default()).await;
+    device.simulator.add_fault(FaultRule::new(
+        Some("/lights"),
+        Trigger::Nth(2),
+        Fault::Drop,
+    ));
+    let light = device.light();
+
+    let events = light
+        .watch(Duration::from_millis(20))
+        .take(4)
+        .collect::<Vec<_>>();
+    let change = async {
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        device.simulator.update_state(|state| {
+            state.lights.lights[0].on = true;
+            state.lights.lights[0].brightness = brightness(70);
+            state.accessory_info.display_name = "Desk".to_string();
+        });
+    };
+    let (events, _) = tokio::join!(events, change);
+
+    assert!(events[0].as_ref().is_err_and(|e| e.is_offline()));
+    let events = events[1..]
+        .iter()
+        .map(|x| x.as_ref().unwrap().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![
+            LightEvent::Power { index: 0, on: true },
+            LightEvent::Brightness {
+                index: 0,
+                value: brightness(70)
+            },
+            LightEvent::DisplayName("Desk".to_string()),
+        ]
+    );
+}
@@ -4,3 +4,4 @@
 
 cargo publish -p open_ecc
 cargo publish -p open_ecc_cli
+cargo publish -p open_ecc_sim